@@ -2,13 +2,75 @@
 // SINYALIST — Build Script (Proto Compilation)
 // =============================================================================
 // Compiles sinyalist_packet.proto into Rust types at build time.
-// In development, we define types manually in main.rs for faster iteration.
-// Enable this for production builds.
+// In development, the types are defined by hand in main.rs for faster
+// iteration (see the inverse `proto-codegen` feature gate there). Enable
+// `proto-codegen` for production builds so the wire types come straight out
+// of the .proto source instead of a hand-kept copy — the two can then be
+// diff-tested against each other.
+//
+// By default the generated Rust lands in OUT_DIR, which makes it invisible
+// to review and impossible to keep in sync with sibling non-Rust
+// implementations of the same signaling protocol. Set SINYALIST_PROTO_OUT=1
+// to instead write the generated module into the checked-in
+// `src/generated/` directory, skipping regeneration when that directory
+// already matches the current .proto (so local `cargo build` runs don't
+// constantly dirty a committed file).
 // =============================================================================
 
+const PROTO_PATH: &str = "../proto/sinyalist_packet.proto";
+const CHECKED_IN_OUT_DIR: &str = "src/generated";
+
 fn main() {
-    // Uncomment for production proto compilation:
-    // prost_build::compile_protos(&["../proto/sinyalist_packet.proto"], &["../proto/"])
-    //     .expect("Failed to compile protobuf definitions");
-    println!("cargo:rerun-if-changed=../proto/sinyalist_packet.proto");
+    println!("cargo:rerun-if-changed={PROTO_PATH}");
+    println!("cargo:rerun-if-env-changed=SINYALIST_PROTO_OUT");
+
+    if std::env::var("CARGO_FEATURE_PROTO_CODEGEN").is_err() {
+        return;
+    }
+
+    let mut config = prost_build::Config::new();
+    // Signaling packets carry peer/session maps; BTreeMap (not HashMap) keeps
+    // map field iteration order deterministic so hashing, signing, and
+    // wire-diffing are reproducible across runs and across machines.
+    config.btree_map(&["."]);
+    // Route the well-known types at their hand-rolled counterparts instead of
+    // pulling in prost-wkt-types, so fields have the same shape whether they
+    // came from codegen or the manual types.
+    config.extern_path(".google.protobuf.Any", "crate::proto::Any");
+    config.extern_path(".google.protobuf.Timestamp", "crate::proto::Timestamp");
+    config.extern_path(".google.protobuf.Duration", "crate::proto::Duration");
+
+    if std::env::var("SINYALIST_PROTO_OUT").is_ok() {
+        compile_checked_in(config);
+    } else {
+        config
+            .compile_protos(&[PROTO_PATH], &["../proto/"])
+            .expect("Failed to compile protobuf definitions");
+    }
+}
+
+/// Regenerates `src/generated/sinyalist.rs` in place, but only when the
+/// `.proto` has actually changed since the last checked-in run — tracked via
+/// a sidecar hash file rather than the generated output's mtime, since the
+/// checkout itself can touch mtimes without changing content.
+fn compile_checked_in(mut config: prost_build::Config) {
+    use sha2::{Digest, Sha256};
+
+    let proto_bytes = std::fs::read(PROTO_PATH).expect("read sinyalist_packet.proto");
+    let hash = format!("{:x}", Sha256::digest(&proto_bytes));
+
+    let out_dir = std::path::Path::new(CHECKED_IN_OUT_DIR);
+    let stamp_path = out_dir.join(".proto.sha256");
+    let up_to_date = out_dir.join("sinyalist.rs").exists()
+        && std::fs::read_to_string(&stamp_path).map(|s| s.trim() == hash).unwrap_or(false);
+    if up_to_date {
+        return;
+    }
+
+    std::fs::create_dir_all(out_dir).expect("create src/generated");
+    config.out_dir(out_dir);
+    config
+        .compile_protos(&[PROTO_PATH], &["../proto/"])
+        .expect("Failed to compile protobuf definitions");
+    std::fs::write(&stamp_path, hash).expect("write proto hash stamp");
 }
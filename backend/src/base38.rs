@@ -0,0 +1,139 @@
+// =============================================================================
+// SINYALIST — base38 codec for SMS transport
+// =============================================================================
+// `CONN_SMS` packets have to survive an SMS gateway that only carries short
+// ASCII, so the protobuf bytes get wrapped in a reversible text codec instead
+// of sent raw. Same grouping scheme as Matter's onboarding payload encoding:
+// 3 input bytes -> 5 base38 characters (38^5 ≈ 79,235,168 > 2^24), a
+// trailing 2-byte group -> 4 characters, and a trailing 1-byte group -> 2
+// characters. Each group is a little-endian radix-38 number, least
+// significant character first.
+// =============================================================================
+
+const ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ-.";
+
+fn char_value(c: u8) -> Option<u32> {
+    ALPHABET.iter().position(|&a| a == c).map(|i| i as u32)
+}
+
+fn encode_group(mut value: u32, out_chars: usize, out: &mut String) {
+    for _ in 0..out_chars {
+        out.push(ALPHABET[(value % 38) as usize] as char);
+        value /= 38;
+    }
+}
+
+/// Encodes arbitrary bytes to a base38 string. Never fails — every byte
+/// sequence has a valid encoding.
+pub fn encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 5);
+    for chunk in data.chunks(3) {
+        let value = chunk.iter().rev().fold(0u32, |acc, &b| (acc << 8) | b as u32);
+        let chars = match chunk.len() {
+            3 => 5,
+            2 => 4,
+            1 => 2,
+            _ => unreachable!("chunks(3) never yields an empty or >3-byte slice"),
+        };
+        encode_group(value, chars, &mut out);
+    }
+    out
+}
+
+/// Decodes a base38 string produced by [`encode`] back to bytes. Rejects
+/// unknown characters and group lengths that don't match the 5/4/2-character
+/// scheme (e.g. a trailing group of 1 or 3 characters can't have come from
+/// `encode`).
+pub fn decode(s: &str) -> Option<Vec<u8>> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len() / 5 * 3 + 3);
+    let mut i = 0;
+    while i < bytes.len() {
+        let remaining = bytes.len() - i;
+        let (group_chars, out_bytes, max_value) = match remaining {
+            n if n >= 5 => (5, 3, 1u64 << 24),
+            4 => (4, 2, 1u64 << 16),
+            2 => (2, 1, 1u64 << 8),
+            _ => return None, // 1 or 3 leftover chars can't be a valid trailing group
+        };
+
+        let mut value: u64 = 0;
+        for (pos, &c) in bytes[i..i + group_chars].iter().enumerate() {
+            let digit = char_value(c)? as u64;
+            value += digit * 38u64.pow(pos as u32);
+        }
+        if value >= max_value {
+            return None; // out-of-range group — not producible by `encode`
+        }
+        for b in 0..out_bytes {
+            out.push(((value >> (b * 8)) & 0xFF) as u8);
+        }
+
+        i += group_chars;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_empty() {
+        assert_eq!(decode(&encode(&[])).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_roundtrip_various_lengths() {
+        for n in 0..20usize {
+            let data: Vec<u8> = (0..n as u8).collect();
+            let encoded = encode(&data);
+            assert_eq!(decode(&encoded).unwrap(), data, "roundtrip failed for {n} bytes");
+        }
+    }
+
+    #[test]
+    fn test_group_sizes() {
+        // 3 bytes -> 5 chars, 2 bytes -> 4 chars, 1 byte -> 2 chars.
+        assert_eq!(encode(&[1, 2, 3]).len(), 5);
+        assert_eq!(encode(&[1, 2]).len(), 4);
+        assert_eq!(encode(&[1]).len(), 2);
+        assert_eq!(encode(&[1, 2, 3, 4, 5, 6]).len(), 10);
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_length() {
+        // A 3-char or 1-char trailing group can't have come from `encode`.
+        assert!(decode("ABC").is_none());
+        assert!(decode("A").is_none());
+        assert!(decode("00000ABC").is_none());
+    }
+
+    #[test]
+    fn test_decode_rejects_unknown_characters() {
+        assert!(decode("ab").is_none()); // lowercase not in the alphabet
+        assert!(decode("A_").is_none());
+    }
+
+    #[test]
+    fn test_decode_rejects_out_of_range_group() {
+        // A 2-char group can encode 0..38^2-1 = 0..1443, but only 0..255 maps
+        // to a real byte from `encode` — anything higher must be rejected.
+        let in_range = encode_group_string(200);
+        assert!(decode(&in_range).is_some());
+        let out_of_range = encode_group_string(1000);
+        assert!(decode(&out_of_range).is_none());
+    }
+
+    fn encode_group_string(value: u32) -> String {
+        let mut out = String::new();
+        encode_group(value, 2, &mut out);
+        out
+    }
+
+    #[test]
+    fn test_known_vector() {
+        // 0,0,0 -> smallest 3-byte group -> "00000"
+        assert_eq!(encode(&[0, 0, 0]), "00000");
+    }
+}
@@ -0,0 +1,415 @@
+// =============================================================================
+// SINYALIST — Cross-node gossip anti-entropy for geo-cluster consensus state
+// =============================================================================
+// Single-node consensus only sees packets that landed on *this* node; behind
+// a load balancer, two nodes can each hold a minority of unique reporters for
+// the same seismic event and never independently cross
+// `CONSENSUS_MIN_DEVICES`. `gossip_worker` (in main.rs) periodically pushes
+// each node's recently-changed `(geo_key, time_bucket) -> keys` state to a
+// random fanout of peers and, in the same round trip, pulls back whatever
+// the peer has that we don't (CRDS-style push/pull anti-entropy).
+//
+// `GeoCluster.keys` is a grow-only set and `total` is always recomputed as
+// `keys.len() + untrusted_keys.len()` from the merged set itself — never
+// taken from a peer's claimed `GossipDelta.total` — so merging is a
+// conflict-free union of keys with a derived count, no vector clocks or
+// last-writer-wins tie-breaking needed, and merging is safe regardless of
+// delivery order or duplication. Trusting a peer's claimed `total` verbatim
+// would let any caller of `POST /v1/gossip/exchange` forge a cluster over
+// `CONSENSUS_MIN_DEVICES` without actually supplying that many keys. The
+// gossip round interval
+// (`GOSSIP_INTERVAL_SECS` in main.rs) doubles as the dampening window: a
+// remotely-accepted packet becomes visible to local consensus checks within
+// one round, so a packet that only crosses `CONSENSUS_WEIGHT_THRESHOLD` once
+// gossiped peer keys are merged in still reaches `ingest()`'s consensus
+// check and routes to `afad_tx`.
+//
+// `GeoCluster.weighted_total` rides along with `keys` as the same kind of
+// grow-only accumulator: a key only ever contributes its weight once, the
+// first time it's unioned in, so summing stays conflict-free the same way
+// the key set itself is.
+//
+// C14: every `GossipDelta` now also carries a `GossipKeyRep` for each key it
+// lists — the sender's own `known_keys` view of that key's age and confirmed
+// history. A receiver reconciles this into its own `known_keys` by taking
+// min(first_seen_ms) (the earliest any node has seen this key is the truest
+// age) and max(confirmed_clusters) (reputation earned anywhere should count
+// everywhere) — both conflict-free merges for the same reason union/max
+// already are. Without this a key this node has never itself seen defaults
+// to `reputation::BASE_WEIGHT` forever, even once it's long-established
+// elsewhere on the network; `first_ms` is reconciled the same way (min), so a
+// cluster's age reflects the earliest report any node received, not just
+// this one's.
+//
+// C17: `merge_delta` re-runs the receiving node's own `trust::TrustPolicy`
+// against every key in `d.keys` instead of taking the sender's `keys` vs.
+// `untrusted_keys` split on faith — a key the sender trusts but we don't
+// (or a forged delta from an unauthenticated peer, see
+// `main::gossip_exchange`) lands in `untrusted_keys` here regardless of
+// which list it arrived in, the same way `process_packet` already filters
+// locally-ingested keys through `trust_policy` before crediting them.
+// =============================================================================
+
+use crate::reputation::{self, KeyReputation};
+use crate::subscribe::ClusterUpdate;
+use crate::trust;
+use crate::GeoCluster;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// One key's reputation as gossiped alongside a `GossipDelta` — just enough
+/// of `KeyReputation` for a peer to reconcile its own `known_keys` entry.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GossipKeyRep {
+    pub key_hex: String,
+    pub first_seen_ms: u64,
+    pub confirmed_clusters: u32,
+}
+
+/// One cluster's state as gossiped over the wire. Keys are hex-encoded
+/// 32-byte Ed25519 public keys since this travels as JSON, not protobuf.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GossipDelta {
+    pub geo_key: u64,
+    pub time_bucket: u64,
+    pub keys: Vec<String>,
+    // C15: unique untrusted keys — unioned into `GeoCluster.untrusted_keys`
+    // the same way `keys` unions into `GeoCluster.keys`, but never credited
+    // toward `weighted_total` (see `trust::TrustPolicy`).
+    pub untrusted_keys: Vec<String>,
+    pub total: u64,
+    pub first_ms: u64,
+    pub reps: Vec<GossipKeyRep>,
+}
+
+/// Request body for `POST /v1/gossip/exchange`: the caller's recent deltas
+/// to merge (push), plus a compact filter of what it already knows (pull) —
+/// `(geo_key, time_bucket, total)` — so the peer only has to reply with
+/// clusters that are actually new to the caller.
+#[derive(Serialize, Deserialize)]
+pub struct GossipExchange {
+    pub push: Vec<GossipDelta>,
+    pub known: Vec<(u64, u64, u64)>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GossipExchangeResp {
+    pub deltas: Vec<GossipDelta>,
+}
+
+/// Merges one gossiped delta into the local cluster map: union of keys
+/// (crediting each newly-unioned key's weight into `weighted_total` exactly
+/// once), `total` recomputed from the merged set, min of `first_ms`. Every
+/// key in `d.keys` is re-checked against `trust_policy` before it's unioned
+/// into `c.keys` — a key the sender considered trusted lands in
+/// `c.untrusted_keys` instead if *this* node's policy disagrees, same as
+/// `process_packet` already does for locally-ingested packets (C17). Also
+/// reconciles every key in `d.reps` into `known_keys` (min `first_seen_ms`,
+/// max `confirmed_clusters`) before `weighted_total` is computed, so a key
+/// this node has never itself seen is credited the age/history it has earned
+/// elsewhere rather than `reputation::BASE_WEIGHT`. All of it conflict-free
+/// regardless of arrival order or duplication. Returns the resulting
+/// `ClusterUpdate` (and bumps `GeoCluster::version`) if the merge actually
+/// changed anything, so the caller can forward it to `subscribe::Hub` — a
+/// re-delivered, already-known delta is a no-op and shouldn't spam
+/// subscribers with an identical update.
+pub fn merge_delta(
+    clusters: &DashMap<(u64, u64), GeoCluster>,
+    known_keys: &DashMap<Vec<u8>, KeyReputation>,
+    trust_policy: &trust::TrustPolicy,
+    d: &GossipDelta,
+    now_ms: u64,
+) -> Option<ClusterUpdate> {
+    for rep in &d.reps {
+        if let Some(k) = decode_key(&rep.key_hex) {
+            merge_reputation(known_keys, k, rep.first_seen_ms, rep.confirmed_clusters, now_ms);
+        }
+    }
+    let mut c = clusters.entry((d.geo_key, d.time_bucket)).or_insert_with(|| GeoCluster {
+        keys: HashSet::new(),
+        untrusted_keys: HashSet::new(),
+        total: 0,
+        first_ms: now_ms,
+        weighted_total: 0.0,
+        credited: false,
+        notified: false,
+        version: 0,
+        max_alert_level: 0,
+    });
+    let mut changed = false;
+    for hex in &d.keys {
+        if let Some(k) = decode_key(hex) {
+            if trust_policy.is_trusted(&k) {
+                if c.keys.insert(k) {
+                    c.weighted_total += reputation::weight_of(known_keys, &k, now_ms);
+                    changed = true;
+                }
+            } else if c.untrusted_keys.insert(k) {
+                changed = true;
+            }
+        }
+    }
+    for hex in &d.untrusted_keys {
+        if let Some(k) = decode_key(hex) {
+            if c.untrusted_keys.insert(k) {
+                changed = true;
+            }
+        }
+    }
+    let recomputed_total = c.keys.len() as u64 + c.untrusted_keys.len() as u64;
+    if recomputed_total != c.total {
+        c.total = recomputed_total;
+        changed = true;
+    }
+    if d.first_ms > 0 && d.first_ms < c.first_ms {
+        c.first_ms = d.first_ms;
+        changed = true;
+    }
+    if !changed {
+        return None;
+    }
+    c.version += 1;
+    Some(ClusterUpdate::from_cluster(d.geo_key, d.time_bucket, &c))
+}
+
+/// Reconciles one remote `KeyReputation` observation into `known_keys`: the
+/// earliest `first_seen_ms` across every node wins (a key can only have been
+/// first seen once, so any node's claim of an earlier time is more correct
+/// than ours), and the highest `confirmed_clusters` wins (confirmations
+/// earned on one node are real everywhere). A key this node has no entry for
+/// yet is simply inserted — a remote node's report is better than defaulting
+/// to `reputation::BASE_WEIGHT` forever.
+fn merge_reputation(
+    known_keys: &DashMap<Vec<u8>, KeyReputation>,
+    key: [u8; 32],
+    first_seen_ms: u64,
+    confirmed_clusters: u32,
+    now_ms: u64,
+) {
+    let mut r = known_keys.entry(key.to_vec()).or_insert_with(|| KeyReputation::new(now_ms));
+    if first_seen_ms > 0 && first_seen_ms < r.first_seen_ms {
+        r.first_seen_ms = first_seen_ms;
+    }
+    if confirmed_clusters > r.confirmed_clusters {
+        r.confirmed_clusters = confirmed_clusters;
+    }
+}
+
+/// Builds the reply to a `GossipExchange`: every local cluster whose `total`
+/// exceeds what the caller already reported knowing about via `known`, each
+/// paired with this node's `known_keys` view of every reporter in it so the
+/// peer can reconcile reputation alongside the cluster itself.
+pub fn diff_against(
+    clusters: &DashMap<(u64, u64), GeoCluster>,
+    known_keys: &DashMap<Vec<u8>, KeyReputation>,
+    known: &[(u64, u64, u64)],
+) -> Vec<GossipDelta> {
+    let known: HashMap<(u64, u64), u64> = known.iter().map(|&(g, t, n)| ((g, t), n)).collect();
+    clusters
+        .iter()
+        .filter(|e| e.value().total > known.get(e.key()).copied().unwrap_or(0))
+        .map(|e| GossipDelta {
+            geo_key: e.key().0,
+            time_bucket: e.key().1,
+            keys: e.value().keys.iter().map(encode_key).collect(),
+            untrusted_keys: e.value().untrusted_keys.iter().map(encode_key).collect(),
+            total: e.value().total,
+            first_ms: e.value().first_ms,
+            reps: e
+                .value()
+                .keys
+                .iter()
+                .filter_map(|k| {
+                    known_keys.get(k.as_slice()).map(|r| GossipKeyRep {
+                        key_hex: encode_key(k),
+                        first_seen_ms: r.first_seen_ms,
+                        confirmed_clusters: r.confirmed_clusters,
+                    })
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+pub fn encode_key(k: &[u8; 32]) -> String {
+    k.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_key(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 {
+        return None;
+    }
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(n: u8) -> [u8; 32] {
+        let mut k = [0u8; 32];
+        k[0] = n;
+        k
+    }
+
+    fn no_known_keys() -> DashMap<Vec<u8>, KeyReputation> {
+        DashMap::new()
+    }
+
+    fn delta(geo_key: u64, time_bucket: u64, keys: Vec<String>, total: u64) -> GossipDelta {
+        GossipDelta { geo_key, time_bucket, keys, untrusted_keys: vec![], total, first_ms: 0, reps: vec![] }
+    }
+
+    #[test]
+    fn test_merge_is_union_of_keys() {
+        let clusters = DashMap::new();
+        let known = no_known_keys();
+        merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &delta(1, 1, vec![encode_key(&key(1))], 1), 0);
+        merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &delta(1, 1, vec![encode_key(&key(2))], 1), 0);
+        let c = clusters.get(&(1, 1)).unwrap();
+        assert_eq!(c.keys.len(), 2, "merging two deltas should union their keys, not overwrite");
+    }
+
+    #[test]
+    fn test_merge_total_is_recomputed_not_trusted_from_peer() {
+        let clusters = DashMap::new();
+        let known = no_known_keys();
+        merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &delta(1, 1, vec![encode_key(&key(1))], 999), 0);
+        assert_eq!(
+            clusters.get(&(1, 1)).unwrap().total, 1,
+            "total must be derived from the merged key set, not a peer's forged claim"
+        );
+    }
+
+    #[test]
+    fn test_merge_untrusted_peer_key_does_not_credit_weighted_total() {
+        let clusters = DashMap::new();
+        let known = no_known_keys();
+        let policy = trust::TrustPolicy::Allowlist(HashSet::from([key(2)]));
+        merge_delta(&clusters, &known, &policy, &delta(1, 1, vec![encode_key(&key(1))], 1), 0);
+        let c = clusters.get(&(1, 1)).unwrap();
+        assert_eq!(c.keys.len(), 0, "a key this node's policy doesn't trust must not join `keys`, even if the peer sent it as trusted");
+        assert_eq!(c.untrusted_keys.len(), 1, "it should land in `untrusted_keys` instead, same as a locally-ingested untrusted key");
+        assert_eq!(c.weighted_total, 0.0, "an untrusted key must never credit weighted_total");
+    }
+
+    #[test]
+    fn test_merge_is_idempotent() {
+        let clusters = DashMap::new();
+        let known = no_known_keys();
+        let d = delta(1, 1, vec![encode_key(&key(1))], 1);
+        let first = merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &d, 0);
+        let second = merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &d, 0);
+        assert!(first.is_some(), "the first merge of a new key/total must report a change");
+        assert!(second.is_none(), "re-merging the identical delta must report no change");
+        let c = clusters.get(&(1, 1)).unwrap();
+        assert_eq!(c.keys.len(), 1, "re-merging the same delta must not duplicate");
+        assert_eq!(c.weighted_total, reputation::BASE_WEIGHT, "re-merging must not re-credit the same key's weight");
+        assert_eq!(c.version, 1, "a no-op re-merge must not bump version");
+    }
+
+    #[test]
+    fn test_merge_credits_each_new_key_weight_exactly_once() {
+        let clusters = DashMap::new();
+        let known = no_known_keys();
+        merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &delta(1, 1, vec![encode_key(&key(1))], 1), 0);
+        merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &delta(1, 1, vec![encode_key(&key(1)), encode_key(&key(2))], 2), 0);
+        assert_eq!(clusters.get(&(1, 1)).unwrap().weighted_total, reputation::BASE_WEIGHT * 2.0);
+    }
+
+    #[test]
+    fn test_merge_takes_first_ms_minimum() {
+        let clusters = DashMap::new();
+        let known = no_known_keys();
+        merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &delta(1, 1, vec![], 1), 1_000);
+        let mut earlier = delta(1, 1, vec![], 1);
+        earlier.first_ms = 500;
+        merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &earlier, 2_000);
+        assert_eq!(clusters.get(&(1, 1)).unwrap().first_ms, 500, "first_ms must be min(), so a peer's earlier report wins");
+        let mut later = delta(1, 1, vec![], 1);
+        later.first_ms = 900;
+        merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &later, 3_000);
+        assert_eq!(clusters.get(&(1, 1)).unwrap().first_ms, 500, "a later first_ms must never roll back an earlier one");
+    }
+
+    #[test]
+    fn test_merge_reconciles_remote_reputation() {
+        let clusters = DashMap::new();
+        let known = no_known_keys();
+        known.insert(key(1).to_vec(), KeyReputation { first_seen_ms: 5_000, confirmed_clusters: 1 });
+        let mut d = delta(1, 1, vec![encode_key(&key(1))], 1);
+        d.reps = vec![GossipKeyRep { key_hex: encode_key(&key(1)), first_seen_ms: 1_000, confirmed_clusters: 9 }];
+        merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &d, 10_000);
+        let r = known.get(&key(1).to_vec()[..]).unwrap();
+        assert_eq!(r.first_seen_ms, 1_000, "remote's earlier first_seen_ms should win");
+        assert_eq!(r.confirmed_clusters, 9, "remote's higher confirmed_clusters should win");
+    }
+
+    #[test]
+    fn test_merge_reputation_inserts_unknown_key() {
+        let clusters = DashMap::new();
+        let known = no_known_keys();
+        let mut d = delta(1, 1, vec![encode_key(&key(1))], 1);
+        d.reps = vec![GossipKeyRep { key_hex: encode_key(&key(1)), first_seen_ms: 1_000, confirmed_clusters: 3 }];
+        merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &d, 10_000);
+        let r = known.get(&key(1).to_vec()[..]).unwrap();
+        assert_eq!(r.confirmed_clusters, 3, "a remote-only key's reputation should be adopted rather than defaulted to BASE_WEIGHT forever");
+    }
+
+    #[test]
+    fn test_diff_against_skips_already_known() {
+        let clusters = DashMap::new();
+        let known = no_known_keys();
+        merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &delta(1, 1, vec![encode_key(&key(1))], 1), 0);
+        let diff = diff_against(&clusters, &known, &[(1, 1, 1)]);
+        assert!(diff.is_empty(), "peer already at total=1 (our actual unique-key count), nothing new to send");
+    }
+
+    #[test]
+    fn test_diff_against_includes_newer_and_unknown_clusters() {
+        let clusters = DashMap::new();
+        let known = no_known_keys();
+        merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &delta(1, 1, vec![encode_key(&key(1)), encode_key(&key(3))], 0), 0);
+        merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &delta(2, 1, vec![encode_key(&key(2))], 0), 0);
+        // Peer reports total=1 for cluster (1,1) (our actual total is 2, so stale) and says nothing about (2,1).
+        let diff = diff_against(&clusters, &known, &[(1, 1, 1)]);
+        assert_eq!(diff.len(), 2, "both the stale-known and entirely-unknown cluster should come back");
+    }
+
+    #[test]
+    fn test_diff_against_includes_reputation_for_each_key() {
+        let clusters = DashMap::new();
+        let known = no_known_keys();
+        known.insert(key(1).to_vec(), KeyReputation { first_seen_ms: 42, confirmed_clusters: 7 });
+        merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &delta(1, 1, vec![encode_key(&key(1))], 1), 0);
+        let diff = diff_against(&clusters, &known, &[]);
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].reps.len(), 1, "the reply should carry this node's reputation view for every key in the cluster");
+        assert_eq!(diff[0].reps[0].confirmed_clusters, 7);
+    }
+
+    #[test]
+    fn test_merge_unions_untrusted_keys_without_crediting_weight() {
+        let clusters = DashMap::new();
+        let known = no_known_keys();
+        let mut d = delta(1, 1, vec![], 0);
+        d.untrusted_keys = vec![encode_key(&key(1))];
+        merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &d, 0);
+        let c = clusters.get(&(1, 1)).unwrap();
+        assert_eq!(c.untrusted_keys.len(), 1, "untrusted keys should still union");
+        assert_eq!(c.weighted_total, 0.0, "untrusted keys must never credit weighted_total");
+    }
+
+    #[test]
+    fn test_decode_key_rejects_wrong_length() {
+        let clusters = DashMap::new();
+        let known = no_known_keys();
+        merge_delta(&clusters, &known, &trust::TrustPolicy::Open, &delta(1, 1, vec!["deadbeef".into()], 1), 0);
+        assert_eq!(clusters.get(&(1, 1)).unwrap().keys.len(), 0, "malformed hex keys should be dropped, not panic");
+    }
+}
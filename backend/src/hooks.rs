@@ -0,0 +1,204 @@
+// =============================================================================
+// SINYALIST — consensus-triggered notification hooks (C16)
+// =============================================================================
+// Before this, the only way to learn a `GeoCluster` reached consensus was to
+// tail logs, poll `/metrics`, or run a `/v1/subscribe` dashboard yourself.
+// `hook_worker` gives an operator a push-based integration point, modeled on
+// VpnCloud's hook scripts: a configured outbound webhook and/or a local
+// command, fired exactly once per cluster the moment it first crosses
+// `CONSENSUS_WEIGHT_THRESHOLD` (see `main.rs`'s `process_packet`, which sets
+// `GeoCluster.notified` the same way it already sets `credited` for
+// reputation — one crossing, one event, no re-firing on every subsequent
+// packet into an already-confirmed cell).
+//
+// `process_packet` only ever `try_send`s a `HookEvent` onto a bounded
+// channel — a slow webhook endpoint or a hung local command backs up this
+// worker's queue, never the ingestion path itself. A full queue drops the
+// event (counted in `Metrics::hooks_dropped`) rather than blocking, the same
+// backpressure tradeoff `afad_tx`/`persist_tx` already make.
+//
+// Configured via `SINYALIST_HOOK_WEBHOOK_URL` (HTTP POST, JSON body) and/or
+// `SINYALIST_HOOK_COMMAND` (spawned with the same data as `SINYALIST_HOOK_*`
+// environment variables) — either, both, or neither may be set; with neither
+// set the worker logs once and exits, the same as `gossip_worker` does when
+// `SINYALIST_GOSSIP_PEERS` is unset.
+// =============================================================================
+
+use crate::Metrics;
+use serde::Serialize;
+use std::process::Stdio;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+/// Everything a hook needs to describe the cluster that just reached
+/// consensus — lat/lon alongside the raw `geo_key`, so a webhook/command
+/// doesn't have to know this server's cell-encoding scheme.
+pub struct HookEvent {
+    pub geo_key: u64,
+    pub time_bucket: u64,
+    pub lat_e7: i32,
+    pub lon_e7: i32,
+    pub confidence: f32,
+    pub reporter_count: u32,
+    pub first_ms: u64,
+}
+
+#[derive(Serialize)]
+struct WebhookBody {
+    geo_key: u64,
+    time_bucket: u64,
+    lat_e7: i32,
+    lon_e7: i32,
+    confidence: f32,
+    reporter_count: u32,
+    first_ms: u64,
+}
+
+impl From<&HookEvent> for WebhookBody {
+    fn from(e: &HookEvent) -> Self {
+        Self {
+            geo_key: e.geo_key,
+            time_bucket: e.time_bucket,
+            lat_e7: e.lat_e7,
+            lon_e7: e.lon_e7,
+            confidence: e.confidence,
+            reporter_count: e.reporter_count,
+            first_ms: e.first_ms,
+        }
+    }
+}
+
+pub async fn hook_worker(mut rx: mpsc::Receiver<HookEvent>, m: Arc<Metrics>) {
+    let webhook_url = std::env::var("SINYALIST_HOOK_WEBHOOK_URL").ok().filter(|s| !s.is_empty());
+    let command = std::env::var("SINYALIST_HOOK_COMMAND").ok().filter(|s| !s.is_empty());
+    if webhook_url.is_none() && command.is_none() {
+        info!("no SINYALIST_HOOK_WEBHOOK_URL/SINYALIST_HOOK_COMMAND set — consensus hooks disabled");
+        return;
+    }
+    info!(webhook=webhook_url.is_some(), command=command.is_some(), "consensus_hooks_enabled");
+
+    let client = match reqwest::Client::builder().timeout(Duration::from_secs(5)).build() {
+        Ok(c) => c,
+        Err(e) => { error!("hook_client_build_failed: {}", e); return; }
+    };
+
+    while let Some(ev) = rx.recv().await {
+        if let Some(url) = &webhook_url {
+            let body = WebhookBody::from(&ev);
+            match client.post(url).json(&body).send().await {
+                Ok(resp) if resp.status().is_success() => m.hooks_fired.fetch_add(1, Ordering::Relaxed),
+                Ok(resp) => { warn!(status=%resp.status(), "hook_webhook_rejected"); m.hooks_dropped.fetch_add(1, Ordering::Relaxed) }
+                Err(e) => { warn!(error=%e, "hook_webhook_failed"); m.hooks_dropped.fetch_add(1, Ordering::Relaxed) }
+            };
+        }
+        if let Some(cmd) = &command {
+            match spawn_command(cmd, &ev).await {
+                Ok(()) => m.hooks_fired.fetch_add(1, Ordering::Relaxed),
+                Err(e) => { warn!(error=%e, "hook_command_failed"); m.hooks_dropped.fetch_add(1, Ordering::Relaxed) }
+            };
+        }
+    }
+}
+
+async fn spawn_command(cmd: &str, ev: &HookEvent) -> std::io::Result<()> {
+    tokio::process::Command::new(cmd)
+        .env("SINYALIST_HOOK_GEO_KEY", ev.geo_key.to_string())
+        .env("SINYALIST_HOOK_TIME_BUCKET", ev.time_bucket.to_string())
+        .env("SINYALIST_HOOK_LAT_E7", ev.lat_e7.to_string())
+        .env("SINYALIST_HOOK_LON_E7", ev.lon_e7.to_string())
+        .env("SINYALIST_HOOK_CONFIDENCE", ev.confidence.to_string())
+        .env("SINYALIST_HOOK_REPORTER_COUNT", ev.reporter_count.to_string())
+        .env("SINYALIST_HOOK_FIRST_MS", ev.first_ms.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::scratch_base_path;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn sample_event() -> HookEvent {
+        HookEvent {
+            geo_key: 42,
+            time_bucket: 7,
+            lat_e7: 410_000_000,
+            lon_e7: 290_000_000,
+            confidence: 0.75,
+            reporter_count: 3,
+            first_ms: 1_700_000_000_000,
+        }
+    }
+
+    #[test]
+    fn test_webhook_body_from_hook_event_maps_every_field() {
+        let ev = sample_event();
+        let body = WebhookBody::from(&ev);
+        assert_eq!(body.geo_key, ev.geo_key);
+        assert_eq!(body.time_bucket, ev.time_bucket);
+        assert_eq!(body.lat_e7, ev.lat_e7);
+        assert_eq!(body.lon_e7, ev.lon_e7);
+        assert_eq!(body.confidence, ev.confidence);
+        assert_eq!(body.reporter_count, ev.reporter_count);
+        assert_eq!(body.first_ms, ev.first_ms);
+    }
+
+    #[tokio::test]
+    async fn test_spawn_command_sets_sinyalist_hook_env_vars() {
+        // `spawn_command` only ever `spawn()`s — it doesn't wait — so a real
+        // process is used here rather than a mock: a tiny shell script that
+        // dumps its environment to a file we can poll, the same scratch-path
+        // convention `storage::test_support` uses for its own temp files.
+        let out_path = scratch_base_path("hooks_spawn_command_env");
+        let script_path = format!("{out_path}.sh");
+        std::fs::write(&script_path, format!("#!/bin/sh\nenv > {out_path}\n")).expect("script should write");
+        let mut perms = std::fs::metadata(&script_path).expect("script metadata").permissions();
+        perms.set_mode(0o755);
+        std::fs::set_permissions(&script_path, perms).expect("script should be made executable");
+
+        let ev = sample_event();
+        spawn_command(&script_path, &ev).await.expect("spawning the script should succeed");
+
+        let mut env_dump = String::new();
+        for _ in 0..50 {
+            if let Ok(s) = std::fs::read_to_string(&out_path) {
+                env_dump = s;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let _ = std::fs::remove_file(&script_path);
+        let _ = std::fs::remove_file(&out_path);
+
+        assert!(env_dump.contains(&format!("SINYALIST_HOOK_GEO_KEY={}", ev.geo_key)));
+        assert!(env_dump.contains(&format!("SINYALIST_HOOK_TIME_BUCKET={}", ev.time_bucket)));
+        assert!(env_dump.contains(&format!("SINYALIST_HOOK_LAT_E7={}", ev.lat_e7)));
+        assert!(env_dump.contains(&format!("SINYALIST_HOOK_LON_E7={}", ev.lon_e7)));
+        assert!(env_dump.contains(&format!("SINYALIST_HOOK_CONFIDENCE={}", ev.confidence)));
+        assert!(env_dump.contains(&format!("SINYALIST_HOOK_REPORTER_COUNT={}", ev.reporter_count)));
+        assert!(env_dump.contains(&format!("SINYALIST_HOOK_FIRST_MS={}", ev.first_ms)));
+    }
+
+    #[tokio::test]
+    async fn test_hook_worker_exits_immediately_when_unconfigured() {
+        // With neither env var set, `hook_worker` must log-and-return before
+        // ever touching `rx` — if it fell through to the `recv().await` loop
+        // instead, this would hang until the timeout fires, since nothing
+        // sends on `tx` and `tx` is kept alive for the duration of the await.
+        std::env::remove_var("SINYALIST_HOOK_WEBHOOK_URL");
+        std::env::remove_var("SINYALIST_HOOK_COMMAND");
+        let (tx, rx) = mpsc::channel(1);
+        let m = Arc::new(Metrics::new());
+        tokio::time::timeout(Duration::from_secs(2), hook_worker(rx, m))
+            .await
+            .expect("hook_worker must return immediately when unconfigured, not block on rx.recv()");
+        drop(tx);
+    }
+}
@@ -0,0 +1,986 @@
+// =============================================================================
+// SINYALIST — Wire Types & Crypto (library half)
+// =============================================================================
+// Everything an embedded signaling client (BLE mesh node, microcontroller)
+// needs to speak the wire protocol and verify peers: the packet types and
+// `verify_sig`. The full ingestion server (axum/tokio, persistence,
+// consensus) lives in `main.rs` and depends on this crate, not the other way
+// around.
+//
+// Enable the `lite` feature for a `no_std` build (plus `alloc`) suited to
+// embedded targets. `lite` is mutually exclusive with `json`/`chrono`, which
+// assume a full std environment.
+// =============================================================================
+#![cfg_attr(feature = "lite", no_std)]
+
+#[cfg(feature = "lite")]
+extern crate alloc;
+
+#[cfg(feature = "lite")]
+use alloc::{format, string::String, vec::Vec};
+#[cfg(feature = "lite")]
+use core::{error::Error, fmt};
+#[cfg(not(feature = "lite"))]
+use std::{error::Error, fmt};
+
+use prost::Message;
+
+// Proto types (matches sinyalist_packet.proto v2).
+// Generated straight from the .proto with `proto-codegen` so the wire types
+// can be diff-tested against the hand-written ones below; hand-written by
+// default for faster iteration.
+// `proto-checked-in` reads the generated module that `SINYALIST_PROTO_OUT=1`
+// writes to `src/generated/` instead of the ephemeral OUT_DIR, so the
+// generated Rust can be browsed, reviewed, and diffed like any other file.
+#[cfg(all(feature = "proto-codegen", feature = "proto-checked-in"))]
+pub mod proto {
+    include!("generated/sinyalist.rs");
+}
+
+#[cfg(all(feature = "proto-codegen", not(feature = "proto-checked-in")))]
+pub mod proto {
+    include!(concat!(env!("OUT_DIR"), "/sinyalist.rs"));
+}
+
+#[cfg(not(feature = "proto-codegen"))]
+pub mod proto {
+    #[cfg(feature = "lite")]
+    use alloc::{format, string::String, vec::Vec};
+    #[cfg(feature = "lite")]
+    use core::{error::Error, fmt};
+    #[cfg(not(feature = "lite"))]
+    use std::{error::Error, fmt};
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum BloodType { BloodUnknown=0, APos=1, ANeg=2, BPos=3, BNeg=4, AbPos=5, AbNeg=6, OPos=7, ONeg=8 }
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum AlertLevel { AlertUnknown=0, AlertTremor=1, AlertModerate=2, AlertSevere=3, AlertCritical=4 }
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum ConnectivityMode { ConnUnknown=0, ConnGrpc=1, ConnSms=2, ConnBleMesh=3, ConnWifiP2p=4 }
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum MessageType { MsgUnknown=0, MsgTrapped=1, MsgMedical=2, MsgSos=3, MsgStatus=4, MsgHeartbeat=5 }
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Priority { PriorityUnknown=0, PriorityCritical=1, PriorityHigh=2, PriorityNormal=3, PriorityLow=4 }
+
+    #[derive(Clone, prost::Message)]
+    pub struct SinyalistPacket {
+        #[prost(fixed64, tag="1")]  pub user_id: u64,
+        #[prost(uint32, tag="2")]   pub device_hash: u32,
+        #[prost(sint32, tag="3")]   pub latitude_e7: i32,
+        #[prost(sint32, tag="4")]   pub longitude_e7: i32,
+        #[prost(float, tag="5")]    pub altitude_m: f32,
+        #[prost(uint32, tag="6")]   pub accuracy_cm: u32,
+        #[prost(int32, tag="7")]    pub floor_number: i32,
+        #[prost(string, tag="8")]   pub room_hint: String,
+        #[prost(enumeration="BloodType", tag="9")]  pub blood_type: i32,
+        #[prost(uint32, tag="10")]  pub pulse_bpm: u32,
+        #[prost(uint32, tag="11")]  pub spo2_percent: u32,
+        #[prost(bool, tag="12")]    pub has_medical_needs: bool,
+        #[prost(uint32, tag="13")]  pub battery_percent: u32,
+        #[prost(enumeration="ConnectivityMode", tag="14")] pub conn: i32,
+        #[prost(enumeration="AlertLevel", tag="15")] pub alert_level: i32,
+        #[prost(fixed64, tag="16")] pub timestamp_ms: u64,
+        #[prost(uint32, tag="17")]  pub quake_duration_s: u32,
+        #[prost(uint32, tag="18")]  pub hop_count: u32,
+        #[prost(fixed32, tag="19")] pub origin_mesh_id: u32,
+        #[prost(uint32, tag="20")]  pub ttl: u32,
+        #[prost(bool, tag="21")]    pub is_trapped: bool,
+        #[prost(uint32, tag="22")]  pub people_count: u32,
+        #[prost(string, tag="23")]  pub sos_message: String,
+        #[prost(bytes, tag="24")]   pub packet_id: Vec<u8>,
+        #[prost(fixed64, tag="25")] pub created_at_ms: u64,
+        #[prost(enumeration="MessageType", tag="26")] pub msg_type: i32,
+        #[prost(enumeration="Priority", tag="27")]    pub priority: i32,
+        #[prost(bytes, tag="28")]   pub ed25519_signature: Vec<u8>,
+        #[prost(bytes, tag="29")]   pub ed25519_public_key: Vec<u8>,
+        #[prost(float, tag="30")]   pub sta_lta_ratio: f32,
+        #[prost(float, tag="31")]   pub peak_accel_g: f32,
+        #[prost(float, tag="32")]   pub dominant_freq_hz: f32,
+        // Extensible payload slot — see `pack`/`unpack` below.
+        #[prost(message, optional, tag="33")] pub payload: Option<Any>,
+        // Standardized, timezone-safe time representation — see the
+        // `chrono` conversions below and `is_expired`.
+        #[prost(message, optional, tag="34")] pub created_at: Option<Timestamp>,
+        #[prost(message, optional, tag="35")] pub ttl_duration: Option<Duration>,
+        // Merkle-batched signing — see `merkle_batch` below. A packet signed
+        // this way leaves `ed25519_signature` empty.
+        #[prost(bytes, tag="36")]  pub merkle_root: Vec<u8>,
+        #[prost(bytes, tag="37")]  pub merkle_signature: Vec<u8>,
+        #[prost(bytes, repeated, tag="38")] pub merkle_proof: Vec<Vec<u8>>,
+        #[prost(uint32, tag="39")] pub leaf_index: u32,
+    }
+
+    /// Hand-rolled stand-in for `google.protobuf.Timestamp`: signed seconds
+    /// since the Unix epoch plus a non-negative nanosecond offset.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, prost::Message)]
+    pub struct Timestamp {
+        #[prost(int64, tag="1")] pub seconds: i64,
+        #[prost(int32, tag="2")] pub nanos: i32,
+    }
+
+    /// Hand-rolled stand-in for `google.protobuf.Duration`: signed seconds
+    /// plus a signed nanosecond offset of the same sign as `seconds`.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, prost::Message)]
+    pub struct Duration {
+        #[prost(int64, tag="1")] pub seconds: i64,
+        #[prost(int32, tag="2")] pub nanos: i32,
+    }
+
+    /// Error converting a [`Timestamp`] to/from [`chrono::DateTime<Utc>`].
+    #[cfg(feature = "chrono")]
+    #[derive(Debug)]
+    pub struct TimestampError(String);
+    #[cfg(feature = "chrono")]
+    impl fmt::Display for TimestampError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+    }
+    #[cfg(feature = "chrono")]
+    impl Error for TimestampError {}
+
+    #[cfg(feature = "chrono")]
+    impl From<chrono::DateTime<chrono::Utc>> for Timestamp {
+        fn from(dt: chrono::DateTime<chrono::Utc>) -> Self {
+            Timestamp { seconds: dt.timestamp(), nanos: dt.timestamp_subsec_nanos() as i32 }
+        }
+    }
+    #[cfg(feature = "chrono")]
+    impl TryFrom<Timestamp> for chrono::DateTime<chrono::Utc> {
+        type Error = TimestampError;
+        fn try_from(ts: Timestamp) -> Result<Self, Self::Error> {
+            if ts.nanos < 0 || ts.nanos > 999_999_999 {
+                return Err(TimestampError(format!("nanos {} out of range [0, 999_999_999]", ts.nanos)));
+            }
+            chrono::DateTime::from_timestamp(ts.seconds, ts.nanos as u32)
+                .ok_or_else(|| TimestampError(format!("seconds {} out of range", ts.seconds)))
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    impl From<chrono::Duration> for Duration {
+        fn from(d: chrono::Duration) -> Self {
+            let seconds = d.num_seconds();
+            let nanos = (d - chrono::Duration::seconds(seconds)).num_nanoseconds().unwrap_or(0) as i32;
+            Duration { seconds, nanos }
+        }
+    }
+    #[cfg(feature = "chrono")]
+    impl TryFrom<Duration> for chrono::Duration {
+        type Error = TimestampError;
+        fn try_from(d: Duration) -> Result<Self, Self::Error> {
+            if d.nanos.signum() != 0 && d.seconds.signum() != 0 && d.nanos.signum() != d.seconds.signum() as i32 {
+                return Err(TimestampError(format!("duration nanos ({}) and seconds ({}) must share a sign", d.nanos, d.seconds)));
+            }
+            chrono::Duration::try_seconds(d.seconds)
+                .and_then(|s| s.checked_add(&chrono::Duration::nanoseconds(d.nanos as i64)))
+                .ok_or_else(|| TimestampError(format!("seconds {} out of range", d.seconds)))
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    impl SinyalistPacket {
+        /// Whether this packet's advertised lifetime (`created_at` + `ttl_duration`)
+        /// has elapsed relative to `chrono::Utc::now()`. Packets with no
+        /// `created_at` are treated as never expiring.
+        pub fn is_expired(&self) -> bool {
+            let Some(created_at) = self.created_at else { return false; };
+            let Ok(created_at): Result<chrono::DateTime<chrono::Utc>, _> = created_at.try_into() else { return true; };
+            let ttl = self.ttl_duration.unwrap_or_default();
+            let Ok(ttl): Result<chrono::Duration, _> = ttl.try_into() else { return true; };
+            chrono::Utc::now() > created_at + ttl
+        }
+    }
+
+    /// Hand-rolled stand-in for `google.protobuf.Any`: a fully-qualified type
+    /// URL plus the encoded message bytes. Lets downstream users extend the
+    /// protocol with message types the core crate doesn't know about, without
+    /// forking `sinyalist_packet.proto`.
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Any {
+        #[prost(string, tag="1")] pub type_url: String,
+        #[prost(bytes, tag="2")]  pub value: Vec<u8>,
+    }
+
+    /// Message types allowed to travel inside an `Any` payload, keyed by
+    /// their fully-qualified `<package>.<Message>` name — the same string
+    /// that trails the `/` in a `type_url`. Extend this whenever a new
+    /// message type needs to be packable. `unpack` does not consult this
+    /// list (it only compares against its own `expected_name`), and the
+    /// build script registers no type set of its own.
+    pub const KNOWN_ANY_TYPES: &[&str] = &["sinyalist.SinyalistPacket", "sinyalist.PacketAck"];
+
+    #[derive(Debug)]
+    pub enum AnyError {
+        /// The type name trailing the Any's `type_url` does not match `T`.
+        TypeMismatch { expected: &'static str, got: String },
+        Decode(prost::DecodeError),
+    }
+    impl fmt::Display for AnyError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                AnyError::TypeMismatch { expected, got } =>
+                    write!(f, "any type mismatch: expected {expected}, got {got}"),
+                AnyError::Decode(e) => write!(f, "any decode failed: {e}"),
+            }
+        }
+    }
+    impl Error for AnyError {}
+
+    /// Packs `msg` into an `Any` with type URL `type.googleapis.com/<package>.<Message>`.
+    pub fn pack<T: prost::Message>(msg: &T, full_name: &str) -> Any {
+        let mut value = Vec::with_capacity(msg.encoded_len());
+        msg.encode(&mut value).expect("Vec<u8> buffer never runs out of capacity");
+        Any { type_url: format!("type.googleapis.com/{full_name}"), value }
+    }
+
+    /// Unpacks an `Any` into `T`, verifying the fully-qualified name
+    /// trailing the URL matches `expected_name` (e.g. `"sinyalist.PacketAck"`)
+    /// before decoding.
+    pub fn unpack<T: prost::Message + Default>(any: &Any, expected_name: &'static str) -> Result<T, AnyError> {
+        let got = any.type_url.rsplit('/').next().unwrap_or(&any.type_url);
+        if got != expected_name {
+            return Err(AnyError::TypeMismatch { expected: expected_name, got: got.to_string() });
+        }
+        T::decode(any.value.as_slice()).map_err(AnyError::Decode)
+    }
+
+    #[derive(Clone, prost::Message)]
+    pub struct PacketAck {
+        #[prost(fixed64, tag="1")] pub user_id: u64,
+        #[prost(fixed64, tag="2")] pub timestamp_ms: u64,
+        #[prost(bool, tag="3")]    pub received: bool,
+        #[prost(string, tag="4")]  pub rescue_eta: String,
+        #[prost(float, tag="5")]   pub confidence: f32,
+        #[prost(string, tag="6")]  pub ingest_id: String,    // C1: server-assigned ID
+        #[prost(string, tag="7")]  pub status: String,       // C1: "accepted" or "processed"
+    }
+
+    // JSON transcoding — browser/WebRTC signaling clients overwhelmingly speak
+    // JSON over WebSockets, so the same server can accept protobuf-framed
+    // native peers and JSON-framed web peers over one endpoint.
+    //
+    // Follows the canonical protobuf-JSON mapping: field names in
+    // lowerCamelCase, `bytes` as base64, 64-bit ints as strings, enums by
+    // their symbolic name — except `createdAt`/`ttlDuration`, which travel
+    // as `{seconds, nanos}` objects with `seconds` as a plain JSON number,
+    // not as the canonical RFC 3339 / `"1.5s"` strings. We transcode through a mirror struct rather than
+    // deriving serde directly on the prost types, since the wire-friendly
+    // shapes (base64 strings, stringified fixed64s, symbolic enum names)
+    // don't match the prost field types one-to-one.
+    //
+    // Requires std (not available under `lite`).
+    #[cfg(feature = "json")]
+    pub mod json {
+        use super::{Any, Duration, PacketAck, SinyalistPacket, Timestamp};
+        use base64::Engine;
+        use serde::{Deserialize, Serialize};
+
+        fn b64_encode(b: &[u8]) -> String { base64::engine::general_purpose::STANDARD.encode(b) }
+        fn b64_decode(s: &str) -> Result<Vec<u8>, JsonError> {
+            base64::engine::general_purpose::STANDARD.decode(s).map_err(|e| JsonError(e.to_string()))
+        }
+
+        /// Error returned by [`SinyalistPacket::from_json`] / [`PacketAck::from_json`].
+        #[derive(Debug)]
+        pub struct JsonError(String);
+        impl std::fmt::Display for JsonError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "{}", self.0) }
+        }
+        impl std::error::Error for JsonError {}
+
+        #[derive(Serialize, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct AnyJson {
+            type_url: String,
+            #[serde(default)]
+            value: String,
+        }
+        impl From<&Any> for AnyJson {
+            fn from(a: &Any) -> Self { AnyJson { type_url: a.type_url.clone(), value: b64_encode(&a.value) } }
+        }
+        impl TryFrom<AnyJson> for Any {
+            type Error = JsonError;
+            fn try_from(j: AnyJson) -> Result<Self, Self::Error> {
+                Ok(Any { type_url: j.type_url, value: b64_decode(&j.value)? })
+            }
+        }
+
+        #[derive(Serialize, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct TimestampJson {
+            seconds: i64,
+            nanos: i32,
+        }
+        impl From<&Timestamp> for TimestampJson {
+            fn from(t: &Timestamp) -> Self { TimestampJson { seconds: t.seconds, nanos: t.nanos } }
+        }
+        impl From<TimestampJson> for Timestamp {
+            fn from(j: TimestampJson) -> Self { Timestamp { seconds: j.seconds, nanos: j.nanos } }
+        }
+
+        #[derive(Serialize, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct DurationJson {
+            seconds: i64,
+            nanos: i32,
+        }
+        impl From<&Duration> for DurationJson {
+            fn from(d: &Duration) -> Self { DurationJson { seconds: d.seconds, nanos: d.nanos } }
+        }
+        impl From<DurationJson> for Duration {
+            fn from(j: DurationJson) -> Self { Duration { seconds: j.seconds, nanos: j.nanos } }
+        }
+
+        #[derive(Serialize, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct PacketJson {
+            user_id: String,
+            #[serde(default)] device_hash: u32,
+            #[serde(default)] latitude_e7: i32,
+            #[serde(default)] longitude_e7: i32,
+            #[serde(default)] altitude_m: f32,
+            #[serde(default)] accuracy_cm: u32,
+            #[serde(default)] floor_number: i32,
+            #[serde(default)] room_hint: String,
+            #[serde(default)] blood_type: String,
+            #[serde(default)] pulse_bpm: u32,
+            #[serde(default)] spo2_percent: u32,
+            #[serde(default)] has_medical_needs: bool,
+            #[serde(default)] battery_percent: u32,
+            #[serde(default)] conn: String,
+            #[serde(default)] alert_level: String,
+            timestamp_ms: String,
+            #[serde(default)] quake_duration_s: u32,
+            #[serde(default)] hop_count: u32,
+            #[serde(default)] origin_mesh_id: u32,
+            #[serde(default)] ttl: u32,
+            #[serde(default)] is_trapped: bool,
+            #[serde(default)] people_count: u32,
+            #[serde(default)] sos_message: String,
+            #[serde(default)] packet_id: String,
+            #[serde(default)] created_at_ms: String,
+            #[serde(default)] msg_type: String,
+            #[serde(default)] priority: String,
+            #[serde(default)] ed25519_signature: String,
+            #[serde(default)] ed25519_public_key: String,
+            #[serde(default)] sta_lta_ratio: f32,
+            #[serde(default)] peak_accel_g: f32,
+            #[serde(default)] dominant_freq_hz: f32,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            payload: Option<AnyJson>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            created_at: Option<TimestampJson>,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            ttl_duration: Option<DurationJson>,
+        }
+
+        macro_rules! enum_name {
+            ($val:expr, $enum:ty, $unknown:expr, [$($variant:ident => $name:expr),+ $(,)?]) => {
+                match $val {
+                    $(x if x == <$enum>::$variant as i32 => $name,)+
+                    _ => $unknown,
+                }
+            };
+        }
+        macro_rules! enum_code {
+            ($name:expr, $enum:ty, $unknown:ident, [$($variant:ident => $str:expr),+ $(,)?]) => {
+                match $name {
+                    $($str => <$enum>::$variant as i32,)+
+                    _ => <$enum>::$unknown as i32,
+                }
+            };
+        }
+
+        fn blood_type_name(v: i32) -> &'static str {
+            enum_name!(v, super::BloodType, "BLOOD_UNKNOWN", [
+                BloodUnknown => "BLOOD_UNKNOWN", APos => "A_POS", ANeg => "A_NEG",
+                BPos => "B_POS", BNeg => "B_NEG", AbPos => "AB_POS", AbNeg => "AB_NEG",
+                OPos => "O_POS", ONeg => "O_NEG",
+            ])
+        }
+        fn blood_type_code(s: &str) -> i32 {
+            enum_code!(s, super::BloodType, BloodUnknown, [
+                BloodUnknown => "BLOOD_UNKNOWN", APos => "A_POS", ANeg => "A_NEG",
+                BPos => "B_POS", BNeg => "B_NEG", AbPos => "AB_POS", AbNeg => "AB_NEG",
+                OPos => "O_POS", ONeg => "O_NEG",
+            ])
+        }
+        fn conn_name(v: i32) -> &'static str {
+            enum_name!(v, super::ConnectivityMode, "CONN_UNKNOWN", [
+                ConnUnknown => "CONN_UNKNOWN", ConnGrpc => "CONN_GRPC", ConnSms => "CONN_SMS",
+                ConnBleMesh => "CONN_BLE_MESH", ConnWifiP2p => "CONN_WIFI_P2P",
+            ])
+        }
+        fn conn_code(s: &str) -> i32 {
+            enum_code!(s, super::ConnectivityMode, ConnUnknown, [
+                ConnUnknown => "CONN_UNKNOWN", ConnGrpc => "CONN_GRPC", ConnSms => "CONN_SMS",
+                ConnBleMesh => "CONN_BLE_MESH", ConnWifiP2p => "CONN_WIFI_P2P",
+            ])
+        }
+        fn alert_level_name(v: i32) -> &'static str {
+            enum_name!(v, super::AlertLevel, "ALERT_UNKNOWN", [
+                AlertUnknown => "ALERT_UNKNOWN", AlertTremor => "ALERT_TREMOR",
+                AlertModerate => "ALERT_MODERATE", AlertSevere => "ALERT_SEVERE", AlertCritical => "ALERT_CRITICAL",
+            ])
+        }
+        fn alert_level_code(s: &str) -> i32 {
+            enum_code!(s, super::AlertLevel, AlertUnknown, [
+                AlertUnknown => "ALERT_UNKNOWN", AlertTremor => "ALERT_TREMOR",
+                AlertModerate => "ALERT_MODERATE", AlertSevere => "ALERT_SEVERE", AlertCritical => "ALERT_CRITICAL",
+            ])
+        }
+        fn msg_type_name(v: i32) -> &'static str {
+            enum_name!(v, super::MessageType, "MSG_UNKNOWN", [
+                MsgUnknown => "MSG_UNKNOWN", MsgTrapped => "MSG_TRAPPED", MsgMedical => "MSG_MEDICAL",
+                MsgSos => "MSG_SOS", MsgStatus => "MSG_STATUS", MsgHeartbeat => "MSG_HEARTBEAT",
+            ])
+        }
+        fn msg_type_code(s: &str) -> i32 {
+            enum_code!(s, super::MessageType, MsgUnknown, [
+                MsgUnknown => "MSG_UNKNOWN", MsgTrapped => "MSG_TRAPPED", MsgMedical => "MSG_MEDICAL",
+                MsgSos => "MSG_SOS", MsgStatus => "MSG_STATUS", MsgHeartbeat => "MSG_HEARTBEAT",
+            ])
+        }
+        fn priority_name(v: i32) -> &'static str {
+            enum_name!(v, super::Priority, "PRIORITY_UNKNOWN", [
+                PriorityUnknown => "PRIORITY_UNKNOWN", PriorityCritical => "PRIORITY_CRITICAL",
+                PriorityHigh => "PRIORITY_HIGH", PriorityNormal => "PRIORITY_NORMAL", PriorityLow => "PRIORITY_LOW",
+            ])
+        }
+        fn priority_code(s: &str) -> i32 {
+            enum_code!(s, super::Priority, PriorityUnknown, [
+                PriorityUnknown => "PRIORITY_UNKNOWN", PriorityCritical => "PRIORITY_CRITICAL",
+                PriorityHigh => "PRIORITY_HIGH", PriorityNormal => "PRIORITY_NORMAL", PriorityLow => "PRIORITY_LOW",
+            ])
+        }
+
+        impl From<&SinyalistPacket> for PacketJson {
+            fn from(p: &SinyalistPacket) -> Self {
+                PacketJson {
+                    user_id: p.user_id.to_string(),
+                    device_hash: p.device_hash,
+                    latitude_e7: p.latitude_e7,
+                    longitude_e7: p.longitude_e7,
+                    altitude_m: p.altitude_m,
+                    accuracy_cm: p.accuracy_cm,
+                    floor_number: p.floor_number,
+                    room_hint: p.room_hint.clone(),
+                    blood_type: blood_type_name(p.blood_type).to_string(),
+                    pulse_bpm: p.pulse_bpm,
+                    spo2_percent: p.spo2_percent,
+                    has_medical_needs: p.has_medical_needs,
+                    battery_percent: p.battery_percent,
+                    conn: conn_name(p.conn).to_string(),
+                    alert_level: alert_level_name(p.alert_level).to_string(),
+                    timestamp_ms: p.timestamp_ms.to_string(),
+                    quake_duration_s: p.quake_duration_s,
+                    hop_count: p.hop_count,
+                    origin_mesh_id: p.origin_mesh_id,
+                    ttl: p.ttl,
+                    is_trapped: p.is_trapped,
+                    people_count: p.people_count,
+                    sos_message: p.sos_message.clone(),
+                    packet_id: b64_encode(&p.packet_id),
+                    created_at_ms: p.created_at_ms.to_string(),
+                    msg_type: msg_type_name(p.msg_type).to_string(),
+                    priority: priority_name(p.priority).to_string(),
+                    ed25519_signature: b64_encode(&p.ed25519_signature),
+                    ed25519_public_key: b64_encode(&p.ed25519_public_key),
+                    sta_lta_ratio: p.sta_lta_ratio,
+                    peak_accel_g: p.peak_accel_g,
+                    dominant_freq_hz: p.dominant_freq_hz,
+                    payload: p.payload.as_ref().map(AnyJson::from),
+                    created_at: p.created_at.as_ref().map(TimestampJson::from),
+                    ttl_duration: p.ttl_duration.as_ref().map(DurationJson::from),
+                }
+            }
+        }
+
+        impl TryFrom<PacketJson> for SinyalistPacket {
+            type Error = JsonError;
+            fn try_from(j: PacketJson) -> Result<Self, Self::Error> {
+                Ok(SinyalistPacket {
+                    user_id: j.user_id.parse().map_err(|_| JsonError(format!("invalid userId {:?}", j.user_id)))?,
+                    device_hash: j.device_hash,
+                    latitude_e7: j.latitude_e7,
+                    longitude_e7: j.longitude_e7,
+                    altitude_m: j.altitude_m,
+                    accuracy_cm: j.accuracy_cm,
+                    floor_number: j.floor_number,
+                    room_hint: j.room_hint,
+                    blood_type: blood_type_code(&j.blood_type),
+                    pulse_bpm: j.pulse_bpm,
+                    spo2_percent: j.spo2_percent,
+                    has_medical_needs: j.has_medical_needs,
+                    battery_percent: j.battery_percent,
+                    conn: conn_code(&j.conn),
+                    alert_level: alert_level_code(&j.alert_level),
+                    timestamp_ms: j.timestamp_ms.parse().map_err(|_| JsonError(format!("invalid timestampMs {:?}", j.timestamp_ms)))?,
+                    quake_duration_s: j.quake_duration_s,
+                    hop_count: j.hop_count,
+                    origin_mesh_id: j.origin_mesh_id,
+                    ttl: j.ttl,
+                    is_trapped: j.is_trapped,
+                    people_count: j.people_count,
+                    sos_message: j.sos_message,
+                    packet_id: b64_decode(&j.packet_id)?,
+                    created_at_ms: j.created_at_ms.parse().map_err(|_| JsonError(format!("invalid createdAtMs {:?}", j.created_at_ms)))?,
+                    msg_type: msg_type_code(&j.msg_type),
+                    priority: priority_code(&j.priority),
+                    ed25519_signature: b64_decode(&j.ed25519_signature)?,
+                    ed25519_public_key: b64_decode(&j.ed25519_public_key)?,
+                    sta_lta_ratio: j.sta_lta_ratio,
+                    peak_accel_g: j.peak_accel_g,
+                    dominant_freq_hz: j.dominant_freq_hz,
+                    payload: j.payload.map(Any::try_from).transpose()?,
+                    created_at: j.created_at.map(Timestamp::from),
+                    ttl_duration: j.ttl_duration.map(Duration::from),
+                    // Merkle-batch fields (tags 36-39) aren't yet carried over
+                    // JSON — see `merkle_batch` in this crate's root; a
+                    // JSON/WebRTC client can't submit a batch-signed packet.
+                    ..Default::default()
+                })
+            }
+        }
+
+        #[derive(Serialize, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct AckJson {
+            user_id: String,
+            timestamp_ms: String,
+            received: bool,
+            #[serde(default)] rescue_eta: String,
+            confidence: f32,
+            #[serde(default)] ingest_id: String,
+            #[serde(default)] status: String,
+        }
+        impl From<&PacketAck> for AckJson {
+            fn from(a: &PacketAck) -> Self {
+                AckJson {
+                    user_id: a.user_id.to_string(),
+                    timestamp_ms: a.timestamp_ms.to_string(),
+                    received: a.received,
+                    rescue_eta: a.rescue_eta.clone(),
+                    confidence: a.confidence,
+                    ingest_id: a.ingest_id.clone(),
+                    status: a.status.clone(),
+                }
+            }
+        }
+        impl TryFrom<AckJson> for PacketAck {
+            type Error = JsonError;
+            fn try_from(j: AckJson) -> Result<Self, Self::Error> {
+                Ok(PacketAck {
+                    user_id: j.user_id.parse().map_err(|_| JsonError(format!("invalid userId {:?}", j.user_id)))?,
+                    timestamp_ms: j.timestamp_ms.parse().map_err(|_| JsonError(format!("invalid timestampMs {:?}", j.timestamp_ms)))?,
+                    received: j.received,
+                    rescue_eta: j.rescue_eta,
+                    confidence: j.confidence,
+                    ingest_id: j.ingest_id,
+                    status: j.status,
+                })
+            }
+        }
+
+        impl SinyalistPacket {
+            pub fn to_json(&self) -> String {
+                serde_json::to_string(&PacketJson::from(self)).expect("PacketJson serialization is infallible")
+            }
+            pub fn from_json(s: &str) -> Result<Self, JsonError> {
+                let j: PacketJson = serde_json::from_str(s).map_err(|e| JsonError(e.to_string()))?;
+                j.try_into()
+            }
+        }
+        impl PacketAck {
+            pub fn to_json(&self) -> String {
+                serde_json::to_string(&AckJson::from(self)).expect("AckJson serialization is infallible")
+            }
+            pub fn from_json(s: &str) -> Result<Self, JsonError> {
+                let j: AckJson = serde_json::from_str(s).map_err(|e| JsonError(e.to_string()))?;
+                j.try_into()
+            }
+        }
+    }
+}
+
+// Merkle-batched signing (amortizes Ed25519 signing cost across a batch of
+// N packets, mirroring how Roughtime servers batch many client requests
+// under one signature).
+//
+// A generator builds a binary Merkle tree over a batch's leaf hashes
+// (`H(0x00 || signing_bytes)`, padded with zero leaves up to the next power
+// of two, internal nodes `H(0x01 || left || right)`), signs only the root
+// once, and attaches each packet its own inclusion proof (`merkle_proof`,
+// `leaf_index`) plus the shared `merkle_root`/`merkle_signature`. `verify_sig`
+// below recomputes the root from a packet's own leaf + proof and checks
+// `merkle_signature` over that root for every packet it is given; results
+// are not cached across a batch, so each packet still costs a handful of
+// SHA-256 hashes plus one Ed25519 check. Only the signing side is amortized.
+pub mod merkle_batch {
+    #[cfg(feature = "lite")]
+    use alloc::vec::Vec;
+    use sha2::{Digest, Sha256};
+
+    pub type Hash = [u8; 32];
+
+    pub fn leaf_hash(signing_bytes: &[u8]) -> Hash {
+        let mut h = Sha256::new();
+        h.update([0x00]);
+        h.update(signing_bytes);
+        h.finalize().into()
+    }
+
+    fn node_hash(left: &Hash, right: &Hash) -> Hash {
+        let mut h = Sha256::new();
+        h.update([0x01]);
+        h.update(left);
+        h.update(right);
+        h.finalize().into()
+    }
+
+    /// Builds a complete binary Merkle tree over `leaves`, padded with zero
+    /// leaves up to the next power of two. Returns the root and, for each
+    /// input leaf (in the same order given), its ordered sibling hashes from
+    /// leaf to root.
+    pub fn build_tree(leaves: &[Hash]) -> (Hash, Vec<Vec<Hash>>) {
+        let n = leaves.len().max(1).next_power_of_two();
+        let mut level: Vec<Hash> = leaves.to_vec();
+        level.resize(n, [0u8; 32]);
+        let mut proofs: Vec<Vec<Hash>> = (0..leaves.len()).map(|_| Vec::new()).collect();
+        let mut indices: Vec<usize> = (0..leaves.len()).collect();
+
+        while level.len() > 1 {
+            let mut next = Vec::with_capacity(level.len() / 2);
+            for pair in level.chunks(2) {
+                next.push(node_hash(&pair[0], &pair[1]));
+            }
+            for (leaf_i, idx) in indices.iter_mut().enumerate() {
+                let sibling = level[*idx ^ 1];
+                proofs[leaf_i].push(sibling);
+                *idx /= 2;
+            }
+            level = next;
+        }
+        (level[0], proofs)
+    }
+
+    /// Recomputes the root from `leaf` + `proof` and checks it against
+    /// `root` — the verifier's half of `build_tree`.
+    pub fn verify_proof(leaf: Hash, leaf_index: u32, proof: &[Hash], root: Hash) -> bool {
+        let mut acc = leaf;
+        let mut idx = leaf_index;
+        for sibling in proof {
+            acc = if idx & 1 == 0 { node_hash(&acc, sibling) } else { node_hash(sibling, &acc) };
+            idx /= 2;
+        }
+        acc == root
+    }
+}
+
+/// Packet bytes to sign/verify against: the packet with every
+/// signature-carrying field (`ed25519_signature` and the `merkle_*` fields,
+/// none of which are known until after signing) cleared.
+fn signing_bytes(p: &proto::SinyalistPacket) -> Vec<u8> {
+    let mut sp = p.clone();
+    sp.ed25519_signature.clear();
+    sp.merkle_root.clear();
+    sp.merkle_signature.clear();
+    sp.merkle_proof.clear();
+    sp.leaf_index = 0;
+    let mut sb = Vec::with_capacity(sp.encoded_len());
+    let _ = sp.encode(&mut sb);
+    sb
+}
+
+/// Verifies a packet's authenticity: either its own Ed25519 signature over
+/// its own bytes (signature field cleared), or — if it carries a
+/// `merkle_signature` instead — that its leaf is included under
+/// `merkle_root` and that root was signed by `ed25519_public_key` (see
+/// `merkle_batch`). Works under `lite` (no_std + alloc) so embedded mesh
+/// nodes can authenticate peers before relaying their packets.
+pub fn verify_sig(p: &proto::SinyalistPacket) -> bool {
+    use ed25519_dalek::{Signature, VerifyingKey, Verifier};
+    let Ok(pk) = <[u8;32]>::try_from(p.ed25519_public_key.as_slice()) else { return false; };
+    let Ok(vk) = VerifyingKey::from_bytes(&pk) else { return false; };
+
+    if !p.merkle_signature.is_empty() {
+        let Ok(root) = <[u8;32]>::try_from(p.merkle_root.as_slice()) else { return false; };
+        let Ok(sg) = <[u8;64]>::try_from(p.merkle_signature.as_slice()) else { return false; };
+        let leaf = merkle_batch::leaf_hash(&signing_bytes(p));
+        let mut proof = Vec::with_capacity(p.merkle_proof.len());
+        for sibling in &p.merkle_proof {
+            let Ok(h) = <[u8;32]>::try_from(sibling.as_slice()) else { return false; };
+            proof.push(h);
+        }
+        if !merkle_batch::verify_proof(leaf, p.leaf_index, &proof, root) { return false; }
+        let sig = Signature::from_bytes(&sg);
+        return vk.verify(&root, &sig).is_ok();
+    }
+
+    if p.ed25519_signature.len() != 64 { return false; }
+    let Ok(sg) = <[u8;64]>::try_from(p.ed25519_signature.as_slice()) else { return false; };
+    let sig = Signature::from_bytes(&sg);
+    vk.verify(&signing_bytes(p), &sig).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_sig_valid_roundtrip() {
+        use ed25519_dalek::{SigningKey, Signer};
+        use rand::rngs::OsRng;
+
+        // Generate a real keypair
+        let sk = SigningKey::generate(&mut OsRng);
+        let vk = sk.verifying_key();
+
+        // Build a packet WITHOUT signature
+        let mut p = proto::SinyalistPacket::default();
+        p.user_id = 42;
+        p.timestamp_ms = 1700000000000;
+        p.latitude_e7 = 410000000;
+        p.longitude_e7 = 290000000;
+        p.packet_id = vec![1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16];
+        p.ed25519_public_key = vk.to_bytes().to_vec();
+
+        // Serialize without signature to get signing bytes
+        let mut signing_bytes = Vec::with_capacity(p.encoded_len());
+        p.encode(&mut signing_bytes).unwrap();
+
+        // Sign
+        let sig = sk.sign(&signing_bytes);
+        p.ed25519_signature = sig.to_bytes().to_vec();
+
+        // Verify
+        assert!(verify_sig(&p), "Valid signature should pass verification");
+    }
+
+    #[test]
+    fn test_verify_sig_detects_tampering() {
+        use ed25519_dalek::{SigningKey, Signer};
+        use rand::rngs::OsRng;
+
+        let sk = SigningKey::generate(&mut OsRng);
+        let vk = sk.verifying_key();
+
+        let mut p = proto::SinyalistPacket::default();
+        p.user_id = 42;
+        p.timestamp_ms = 1700000000000;
+        p.ed25519_public_key = vk.to_bytes().to_vec();
+
+        let mut signing_bytes = Vec::with_capacity(p.encoded_len());
+        p.encode(&mut signing_bytes).unwrap();
+
+        let sig = sk.sign(&signing_bytes);
+        p.ed25519_signature = sig.to_bytes().to_vec();
+
+        // Tamper with a field AFTER signing
+        p.user_id = 99;
+
+        assert!(!verify_sig(&p), "Tampered packet should fail verification");
+    }
+
+    #[test]
+    fn test_verify_sig_rejects_wrong_lengths() {
+        let mut p = proto::SinyalistPacket::default();
+        p.ed25519_public_key = vec![0u8; 16]; // Wrong length
+        p.ed25519_signature = vec![0u8; 64];
+        assert!(!verify_sig(&p));
+    }
+
+    #[test]
+    fn test_verify_sig_rejects_empty() {
+        let p = proto::SinyalistPacket::default();
+        assert!(!verify_sig(&p));
+    }
+
+    fn merkle_batch_packet(sk: &ed25519_dalek::SigningKey, idx: usize, proofs: &[Vec<merkle_batch::Hash>], root: merkle_batch::Hash) -> proto::SinyalistPacket {
+        use ed25519_dalek::Signer;
+        let mut p = proto::SinyalistPacket::default();
+        p.user_id = idx as u64;
+        p.ed25519_public_key = sk.verifying_key().to_bytes().to_vec();
+        p.merkle_root = root.to_vec();
+        p.merkle_signature = sk.sign(&root).to_bytes().to_vec();
+        p.merkle_proof = proofs[idx].iter().map(|h| h.to_vec()).collect();
+        p.leaf_index = idx as u32;
+        p
+    }
+
+    #[test]
+    fn test_merkle_batch_build_tree_is_pow2_padded() {
+        let leaves = vec![[1u8; 32], [2u8; 32], [3u8; 32]];
+        let (_root, proofs) = merkle_batch::build_tree(&leaves);
+        // 3 leaves pad to 4 — every proof should be 2 levels deep.
+        assert_eq!(proofs.len(), 3);
+        assert!(proofs.iter().all(|p| p.len() == 2));
+    }
+
+    #[test]
+    fn test_merkle_batch_verify_proof_roundtrips_every_leaf() {
+        let leaves: Vec<merkle_batch::Hash> = (0..5u8).map(|i| [i; 32]).collect();
+        let (root, proofs) = merkle_batch::build_tree(&leaves);
+        for (i, leaf) in leaves.iter().enumerate() {
+            assert!(merkle_batch::verify_proof(*leaf, i as u32, &proofs[i], root), "leaf {i} should verify");
+        }
+    }
+
+    #[test]
+    fn test_merkle_batch_verify_proof_rejects_wrong_root() {
+        let leaves: Vec<merkle_batch::Hash> = (0..4u8).map(|i| [i; 32]).collect();
+        let (_root, proofs) = merkle_batch::build_tree(&leaves);
+        let wrong_root = [0xffu8; 32];
+        assert!(!merkle_batch::verify_proof(leaves[0], 0, &proofs[0], wrong_root));
+    }
+
+    #[test]
+    fn test_verify_sig_accepts_valid_merkle_batch_packet() {
+        use ed25519_dalek::SigningKey;
+        use rand::rngs::OsRng;
+
+        let sk = SigningKey::generate(&mut OsRng);
+        // Build every packet in the batch (minus signature fields) first, so
+        // each one's own bytes feed its own leaf hash.
+        let mut packets: Vec<proto::SinyalistPacket> = (0..4)
+            .map(|i| {
+                let mut p = proto::SinyalistPacket::default();
+                p.user_id = i as u64;
+                p.ed25519_public_key = sk.verifying_key().to_bytes().to_vec();
+                p
+            })
+            .collect();
+        let leaves: Vec<merkle_batch::Hash> = packets.iter().map(|p| merkle_batch::leaf_hash(&signing_bytes(p))).collect();
+        let (root, proofs) = merkle_batch::build_tree(&leaves);
+        for (i, p) in packets.iter_mut().enumerate() {
+            *p = merkle_batch_packet(&sk, i, &proofs, root);
+        }
+
+        for p in &packets {
+            assert!(verify_sig(p), "valid merkle-batched packet should verify");
+        }
+    }
+
+    #[test]
+    fn test_verify_sig_rejects_merkle_batch_packet_with_tampered_proof() {
+        use ed25519_dalek::SigningKey;
+        use rand::rngs::OsRng;
+
+        let sk = SigningKey::generate(&mut OsRng);
+        let packets: Vec<proto::SinyalistPacket> = (0..4)
+            .map(|i| {
+                let mut p = proto::SinyalistPacket::default();
+                p.user_id = i as u64;
+                p.ed25519_public_key = sk.verifying_key().to_bytes().to_vec();
+                p
+            })
+            .collect();
+        let leaves: Vec<merkle_batch::Hash> = packets.iter().map(|p| merkle_batch::leaf_hash(&signing_bytes(p))).collect();
+        let (root, proofs) = merkle_batch::build_tree(&leaves);
+        let mut p = merkle_batch_packet(&sk, 1, &proofs, root);
+        p.merkle_proof[0][0] ^= 0xff;
+
+        assert!(!verify_sig(&p), "a tampered inclusion proof must not verify");
+    }
+
+    #[test]
+    fn test_any_pack_unpack_roundtrip() {
+        let ack = proto::PacketAck { user_id: 7, status: "accepted".into(), ..Default::default() };
+        let any = proto::pack(&ack, "sinyalist.PacketAck");
+        assert_eq!(any.type_url, "type.googleapis.com/sinyalist.PacketAck");
+        let back: proto::PacketAck = proto::unpack(&any, "sinyalist.PacketAck").unwrap();
+        assert_eq!(back.user_id, 7);
+        assert_eq!(back.status, "accepted");
+    }
+
+    #[test]
+    fn test_any_unpack_rejects_type_mismatch() {
+        let ack = proto::PacketAck::default();
+        let any = proto::pack(&ack, "sinyalist.PacketAck");
+        let err = proto::unpack::<proto::SinyalistPacket>(&any, "sinyalist.SinyalistPacket").unwrap_err();
+        assert!(matches!(err, proto::AnyError::TypeMismatch { .. }));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_packet_json_roundtrip_preserves_fields() {
+        let mut p = proto::SinyalistPacket::default();
+        p.user_id = u64::MAX;
+        p.timestamp_ms = 1_700_000_000_123;
+        p.is_trapped = true;
+        p.blood_type = proto::BloodType::ONeg as i32;
+        p.packet_id = vec![1, 2, 3, 4];
+        let json = p.to_json();
+        assert!(json.contains("\"userId\":\"18446744073709551615\""));
+        assert!(json.contains("\"bloodType\":\"O_NEG\""));
+        let back = proto::SinyalistPacket::from_json(&json).unwrap();
+        assert_eq!(back.user_id, p.user_id);
+        assert_eq!(back.timestamp_ms, p.timestamp_ms);
+        assert_eq!(back.blood_type, p.blood_type);
+        assert_eq!(back.packet_id, p.packet_id);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_packet_json_roundtrip_preserves_created_at_and_ttl() {
+        let mut p = proto::SinyalistPacket::default();
+        p.user_id = 1;
+        p.timestamp_ms = 1_700_000_000_000;
+        p.created_at = Some(proto::Timestamp { seconds: 1_700_000_000, nanos: 42 });
+        p.ttl_duration = Some(proto::Duration { seconds: 60, nanos: 0 });
+        let json = p.to_json();
+        let back = proto::SinyalistPacket::from_json(&json).unwrap();
+        assert_eq!(back.created_at, p.created_at);
+        assert_eq!(back.ttl_duration, p.ttl_duration);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_packet_json_rejects_invalid_user_id() {
+        let json = r#"{"userId":"not-a-number","timestampMs":"1"}"#;
+        assert!(proto::SinyalistPacket::from_json(json).is_err());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_timestamp_chrono_roundtrip() {
+        let dt = chrono::Utc::now();
+        let ts: proto::Timestamp = dt.into();
+        let back: chrono::DateTime<chrono::Utc> = ts.try_into().unwrap();
+        // Sub-nanosecond precision is lost going through i64 millis elsewhere
+        // in this crate, but the Timestamp conversion itself is exact.
+        assert_eq!(dt.timestamp(), back.timestamp());
+        assert_eq!(dt.timestamp_subsec_nanos(), back.timestamp_subsec_nanos());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_timestamp_rejects_out_of_range_nanos() {
+        let ts = proto::Timestamp { seconds: 0, nanos: -1 };
+        let result: Result<chrono::DateTime<chrono::Utc>, _> = ts.try_into();
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_packet_is_expired() {
+        let mut p = proto::SinyalistPacket::default();
+        p.created_at = Some((chrono::Utc::now() - chrono::Duration::seconds(120)).into());
+        p.ttl_duration = Some(chrono::Duration::seconds(60).into());
+        assert!(p.is_expired(), "packet created 2 minutes ago with a 60s TTL should be expired");
+
+        p.ttl_duration = Some(chrono::Duration::seconds(600).into());
+        assert!(!p.is_expired(), "packet created 2 minutes ago with a 10-minute TTL should not be expired");
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_packet_without_created_at_never_expires() {
+        let p = proto::SinyalistPacket::default();
+        assert!(!p.is_expired());
+    }
+}
@@ -6,14 +6,39 @@
 //   C2: Strict Ed25519 verification (REQUIRED, not optional)
 //   C3: Confidence scoring tested — dedup does NOT inflate
 //   C4: Structured logs + counters for all drop/accept paths
+//   C5: Tamper-evident Merkle log over the persist path + proof endpoint
+//   C6: Cross-node gossip anti-entropy so consensus sees the global cluster
+//   C7: SMS-friendly base38 text transport, same ingest pipeline underneath
+//   C8: Precise TTL eviction for dedup/rate-limit maps (was: 60s retain scans)
+//   C9: Pluggable indexed storage backend (NDJSON/SQLite/LMDB) + GET /v1/query
+//   C10: AEAD-seal PII/medical fields before they reach a persist backend
+//   C11: Stake-weighted reporter reputation — consensus needs summed key
+//        weight, not raw unique-key count, so a Sybil flood of fresh keys
+//        can't reach it the way a handful of established devices can
+//   C12: GET /v1/subscribe — live SSE push of cluster updates to AFAD
+//        dashboards, filterable by geo bounding box / min alert level
+//   C13: POST /v1/ingest/batch — verify a burst of packets' Ed25519
+//        signatures as one batch instead of one curve op per packet
+//   C14: Gossip deltas reconcile first_ms and KeyReputation across nodes,
+//        not just the key set/total — see gossip.rs
+//   C15: Trusted-key modes (open/allowlist/shared-secret) so a Sybil flood
+//        of fresh keypairs can't mint its own consensus — see trust.rs
+//   C16: Consensus-triggered webhook/local-command notification hooks,
+//        fired once per cluster on its first CONSENSUS_WEIGHT_THRESHOLD
+//        crossing — see hooks.rs
+//   C17: GET /v1/reporters — top-N representative reporters per cluster,
+//        sampled by reputation weight via Solana-style weighted shuffle
+//        instead of HashSet arrival order — see weighted_shuffle.rs
 // =============================================================================
 
-use axum::{Router, extract::State, http::{StatusCode, HeaderMap, HeaderValue}, response::IntoResponse, routing::{get, post}, Json};
+use axum::{Router, extract::{State, Query}, http::{StatusCode, HeaderMap, HeaderValue}, response::IntoResponse, routing::{get, post}, Json};
 use bytes::Bytes;
 use dashmap::DashMap;
+use ed25519_dalek::{SigningKey, Signer};
 use prost::Message;
+use rand::seq::SliceRandom;
 use serde::{Serialize, Deserialize};
-use std::{sync::Arc, time::Duration, net::SocketAddr, collections::HashSet};
+use std::{sync::{Arc, RwLock}, time::Duration, net::SocketAddr, collections::HashSet};
 use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::sync::mpsc;
 use tokio::io::AsyncWriteExt;
@@ -21,96 +46,136 @@ use tower::ServiceBuilder;
 use tower_http::{compression::CompressionLayer, trace::TraceLayer};
 use tracing::{info, warn, error, instrument};
 
-// Proto types (matches sinyalist_packet.proto v2)
-pub mod proto {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, prost::Enumeration)]
-    #[repr(i32)]
-    pub enum BloodType { BloodUnknown=0, APos=1, ANeg=2, BPos=3, BNeg=4, AbPos=5, AbNeg=6, OPos=7, ONeg=8 }
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, prost::Enumeration)]
-    #[repr(i32)]
-    pub enum AlertLevel { AlertUnknown=0, AlertTremor=1, AlertModerate=2, AlertSevere=3, AlertCritical=4 }
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, prost::Enumeration)]
-    #[repr(i32)]
-    pub enum ConnectivityMode { ConnUnknown=0, ConnGrpc=1, ConnSms=2, ConnBleMesh=3, ConnWifiP2p=4 }
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, prost::Enumeration)]
-    #[repr(i32)]
-    pub enum MessageType { MsgUnknown=0, MsgTrapped=1, MsgMedical=2, MsgSos=3, MsgStatus=4, MsgHeartbeat=5 }
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, prost::Enumeration)]
-    #[repr(i32)]
-    pub enum Priority { PriorityUnknown=0, PriorityCritical=1, PriorityHigh=2, PriorityNormal=3, PriorityLow=4 }
-
-    #[derive(Clone, prost::Message)]
-    pub struct SinyalistPacket {
-        #[prost(fixed64, tag="1")]  pub user_id: u64,
-        #[prost(uint32, tag="2")]   pub device_hash: u32,
-        #[prost(sint32, tag="3")]   pub latitude_e7: i32,
-        #[prost(sint32, tag="4")]   pub longitude_e7: i32,
-        #[prost(float, tag="5")]    pub altitude_m: f32,
-        #[prost(uint32, tag="6")]   pub accuracy_cm: u32,
-        #[prost(int32, tag="7")]    pub floor_number: i32,
-        #[prost(string, tag="8")]   pub room_hint: String,
-        #[prost(enumeration="BloodType", tag="9")]  pub blood_type: i32,
-        #[prost(uint32, tag="10")]  pub pulse_bpm: u32,
-        #[prost(uint32, tag="11")]  pub spo2_percent: u32,
-        #[prost(bool, tag="12")]    pub has_medical_needs: bool,
-        #[prost(uint32, tag="13")]  pub battery_percent: u32,
-        #[prost(enumeration="ConnectivityMode", tag="14")] pub conn: i32,
-        #[prost(enumeration="AlertLevel", tag="15")] pub alert_level: i32,
-        #[prost(fixed64, tag="16")] pub timestamp_ms: u64,
-        #[prost(uint32, tag="17")]  pub quake_duration_s: u32,
-        #[prost(uint32, tag="18")]  pub hop_count: u32,
-        #[prost(fixed32, tag="19")] pub origin_mesh_id: u32,
-        #[prost(uint32, tag="20")]  pub ttl: u32,
-        #[prost(bool, tag="21")]    pub is_trapped: bool,
-        #[prost(uint32, tag="22")]  pub people_count: u32,
-        #[prost(string, tag="23")]  pub sos_message: String,
-        #[prost(bytes, tag="24")]   pub packet_id: Vec<u8>,
-        #[prost(fixed64, tag="25")] pub created_at_ms: u64,
-        #[prost(enumeration="MessageType", tag="26")] pub msg_type: i32,
-        #[prost(enumeration="Priority", tag="27")]    pub priority: i32,
-        #[prost(bytes, tag="28")]   pub ed25519_signature: Vec<u8>,
-        #[prost(bytes, tag="29")]   pub ed25519_public_key: Vec<u8>,
-        #[prost(float, tag="30")]   pub sta_lta_ratio: f32,
-        #[prost(float, tag="31")]   pub peak_accel_g: f32,
-        #[prost(float, tag="32")]   pub dominant_freq_hz: f32,
-    }
-
-    #[derive(Clone, prost::Message)]
-    pub struct PacketAck {
-        #[prost(fixed64, tag="1")] pub user_id: u64,
-        #[prost(fixed64, tag="2")] pub timestamp_ms: u64,
-        #[prost(bool, tag="3")]    pub received: bool,
-        #[prost(string, tag="4")]  pub rescue_eta: String,
-        #[prost(float, tag="5")]   pub confidence: f32,
-        #[prost(string, tag="6")]  pub ingest_id: String,    // C1: server-assigned ID
-        #[prost(string, tag="7")]  pub status: String,       // C1: "accepted" or "processed"
-    }
-}
+// Wire types and `verify_sig` live in lib.rs now, so embedded signaling
+// clients can depend on `sinyalist_ingest` for just the protocol without
+// pulling in axum/tokio/persistence. See lib.rs for the `lite` (no_std)
+// build profile.
+use sinyalist_ingest::{proto, verify_sig};
+
+// Merkle Mountain Range over the persist path — server-only (needs the
+// persisted record bytes and a server signing key), so it lives in the
+// binary crate rather than lib.rs.
+mod merkle;
+use merkle::MerkleLog;
+
+// Cross-node gossip anti-entropy over `clusters` (C6) — server-only, lives
+// in the binary crate alongside the state it merges into.
+mod gossip;
+
+// base38 codec for the SMS transport (C7) — pure, so it'd fit in lib.rs, but
+// it only has one caller (`ingest_sms` below) so it stays local for now.
+mod base38;
+
+// TTL map with precise, O(expired) eviction (C8) — replaces the fixed-60s
+// `retain` scans for `dedup` and the rate limiters.
+mod ttl_map;
+use ttl_map::TtlMap;
+
+// Pluggable indexed persist backend (C9) — NDJSON stays the default, SQLite
+// and LMDB adapters are opt-in via cargo feature + SINYALIST_STORAGE_BACKEND.
+mod storage;
+use storage::{KeyRing, PacketRecord, Payload, PersistBackend};
+
+// Stake-weighted reporter reputation (C11) — server-only, threads through
+// `known_keys` alongside the same DashMap `gossip` already merges weight
+// into.
+mod reputation;
+use reputation::KeyReputation;
+
+// Live cluster subscriptions for AFAD dashboards (C12) — server-only, sits
+// on top of `clusters` the same way `gossip` and `reputation` do.
+mod subscribe;
+
+// Batched Ed25519 verification (C13) — server-only; `verify_sig` (lib.rs)
+// stays the single-packet path embedded clients use, this is purely an
+// ingest-side throughput optimization over the same signing-bytes convention.
+mod sigverify;
+
+// Trusted-key modes for Sybil-resistant consensus (C15)
+mod trust;
+
+// Consensus-triggered webhook/local-command notification hooks (C16)
+mod hooks;
+
+// Weighted-random reporter sampling for GET /v1/reporters (C17) — pure, so
+// it'd fit in lib.rs, but its only caller is server-side like sigverify/
+// subscribe, so it stays here alongside them.
+mod weighted_shuffle;
 
 // Geo-cluster: grid-cell confidence scoring (C3)
+// FIX: old divisor 9000 → cells were ~90 km wide (9000 * 1e-7 deg ≈ 0.09°
+// ≈ ~10 km latitude, even larger in practice).  Correct divisor for ~1 km
+// cells: 1 degree ≈ 111 000 m, so 1 km ≈ 0.009° = 90 000 units in e7.
+// Using 90_000 gives cells of ~1 km × ~1 km near Istanbul (41°N).
+const GEO_CELL_SIZE_E7: i32 = 90_000;
+
 fn geo_key(lat_e7: i32, lon_e7: i32) -> u64 {
-    // FIX: old divisor 9000 → cells were ~90 km wide (9000 * 1e-7 deg ≈ 0.09°
-    // ≈ ~10 km latitude, even larger in practice).  Correct divisor for ~1 km
-    // cells: 1 degree ≈ 111 000 m, so 1 km ≈ 0.009° = 90 000 units in e7.
-    // Using 90_000 gives cells of ~1 km × ~1 km near Istanbul (41°N).
-    let la = (lat_e7 / 90_000) as i64;
-    let lo = (lon_e7 / 90_000) as i64;
+    let la = (lat_e7 / GEO_CELL_SIZE_E7) as i64;
+    let lo = (lon_e7 / GEO_CELL_SIZE_E7) as i64;
     ((la as u64) << 32) | (lo as u64 & 0xFFFFFFFF)
 }
+
+// C12: the inverse of `geo_key` — recovers the (lat_e7, lon_e7) lower-left
+// corner of the cell a key encodes, so `/v1/subscribe`'s bounding-box filter
+// can test a cluster for overlap without `GeoCluster` itself needing to
+// remember the lat/lon it was built from.
+fn decode_geo_key(key: u64) -> (i32, i32) {
+    let la = (key >> 32) as u32 as i32;
+    let lo = (key & 0xFFFF_FFFF) as u32 as i32;
+    (la * GEO_CELL_SIZE_E7, lo * GEO_CELL_SIZE_E7)
+}
+
 fn time_bucket(ms: u64) -> u64 { ms / 60_000 }
 
 #[derive(Default)]
-struct GeoCluster { keys: HashSet<[u8;32]>, total: u64, first_ms: u64 }
+struct GeoCluster {
+    keys: HashSet<[u8;32]>,
+    // C15: unique untrusted keys that have reported into this cluster —
+    // tracked the same way `keys` is (grow-only set, union on gossip merge)
+    // but excluded from `weighted_total`/consensus. Surfaced as
+    // `untrusted_reporters` via `subscribe::ClusterUpdate` so an operator can
+    // see what trust::TrustPolicy is filtering out without it silently
+    // vanishing from the API.
+    untrusted_keys: HashSet<[u8;32]>,
+    total: u64,
+    first_ms: u64,
+    // C11: sum of each unique key's `reputation::weight()` at the moment it
+    // was first inserted into `keys` — grow-only just like `keys` itself, so
+    // CRDT-merging (see `gossip::merge_delta`) stays a conflict-free union
+    // regardless of arrival order or duplication.
+    weighted_total: f32,
+    // C11: whether this cluster has already credited `confirmed_clusters`
+    // history to its reporters — set once, the first time `weighted_total`
+    // crosses `CONSENSUS_WEIGHT_THRESHOLD`, so a key isn't re-credited every
+    // subsequent packet into an already-confirmed cluster.
+    credited: bool,
+    // C16: whether this cluster has already fired its `hooks::hook_worker`
+    // notification — set once, the same crossing `credited` is, so a
+    // configured webhook/command fires exactly once per cluster rather than
+    // once per packet into an already-confirmed cell. Node-local like
+    // `credited`, not reconciled by `gossip::merge_delta`.
+    notified: bool,
+    // C12: bumped on every mutation (see `process_packet` and
+    // `gossip::merge_delta`) — `subscribe::Hub` tags each broadcast update
+    // with the version it was built from so a resyncing `/v1/subscribe`
+    // client can tell whether it's already seen this state.
+    version: u64,
+    // C12: highest `alert_level` any packet into this cluster has carried —
+    // lets `/v1/subscribe`'s `min_alert_level` filter work without needing
+    // the raw packets themselves.
+    max_alert_level: i32,
+}
 impl GeoCluster {
-    // C3: Confidence increases only with UNIQUE independently signed reports
-    // Duplicates (same public key) do NOT inflate confidence
+    // C3/C11: Confidence increases with summed reporter WEIGHT, not raw
+    // unique-key count — duplicates (same public key) still do NOT inflate
+    // confidence, since a key only contributes its weight once (on first
+    // insertion into `keys`).
     fn confidence(&self) -> f32 {
-        let unique = self.keys.len() as f32;
-        if unique == 0.0 { return 0.0; }
-        // Spam detection: if total reports greatly exceed unique reporters, penalize
-        let spam_factor = if self.total as f32 > unique * 3.0 { 0.5 } else { 1.0 };
-        // Log-scale: 1 reporter=0.33, 3=0.70, 7=0.98, 8+=1.0
-        ((unique.ln() + 1.0) / 3.0 * spam_factor).min(1.0)
+        if self.weighted_total <= 0.0 { return 0.0; }
+        // Spam detection: if total reports greatly exceed reporter weight, penalize
+        let spam_factor = if self.total as f32 > self.weighted_total * 3.0 { 0.5 } else { 1.0 };
+        // Log-scale: weight 1=0.33, 3=0.70, 7=0.98, 8+=1.0
+        ((self.weighted_total.ln() + 1.0) / 3.0 * spam_factor).min(1.0)
     }
 }
 
@@ -120,32 +185,116 @@ const RL_PER_KEY: u32 = 30;
 const RL_PER_GEO: u32 = 500;
 const MAX_PKT: usize = 1024;
 const DEDUP_TTL: u64 = 300_000;
+// C8: how long a rate-limit entry survives without being touched again.
+// Matches the old eviction task's `RL_WINDOW*2` retain threshold, but now
+// anchored to last access (TtlMap bumps it on every check) rather than last
+// window reset — an actively-hit key never expires mid-use.
+const RL_IDLE_TTL: u64 = RL_WINDOW * 2;
 // C2: Schema version enforcement
 const SCHEMA_VERSION: &str = "2.0";
 
-// Consensus: minimum unique devices in a geo cell within a time window
-// before a cluster is considered a real seismic event.
+// Consensus: minimum summed reporter WEIGHT (C11) in a geo cell within a
+// time window before a cluster is considered a real seismic event. Chosen to
+// match the old flat-count threshold of 3 for the common case of all-fresh
+// keys (each weighing `reputation::BASE_WEIGHT` == 1.0), while also letting
+// fewer, longer-lived/previously-confirmed keys reach it on their own.
 // Below this threshold the packet is accepted but cluster is marked unconfirmed.
-const CONSENSUS_MIN_DEVICES: usize = 3;
+//
+// This threshold alone does NOT stop a Sybil flood: 3 fresh keys still sum
+// to exactly 3.0, same as the old flat count — weighting only lets
+// *established* reporters reach consensus with fewer devices, it doesn't
+// raise the bar for an attacker who can mint keypairs for free. In the
+// default `TrustPolicy::Open`, that bar isn't raised at all; actual Sybil
+// resistance against free key minting comes from running in `Allowlist` or
+// `SharedSecret` trust mode (trust.rs, C15), which excludes untrusted keys
+// from `weighted_total` entirely rather than merely weighting them down.
+const CONSENSUS_WEIGHT_THRESHOLD: f32 = 3.0;
 
 // Timestamp acceptance window: reject packets whose created_at_ms is more than
 // 5 minutes in the past or 60 seconds in the future (replay + clock-skew protection).
 const TIMESTAMP_PAST_WINDOW_MS: u64  = 5 * 60_000; // 5 minutes
 const TIMESTAMP_FUTURE_WINDOW_MS: u64 = 60_000;     // 60 seconds
 
-// Persist log file path (NDJSON — one JSON line per packet)
-const PERSIST_LOG_PATH: &str = "sinyalist_packets.ndjson";
+// Persist backend base path — each backend appends its own extension
+// (`storage::ndjson` -> ".ndjson", `storage::sqlite` -> ".sqlite3",
+// `storage::lmdb` -> "_lmdb/"). Backend choice itself is
+// SINYALIST_STORAGE_BACKEND (see storage::build_backend).
+const PERSIST_BASE_PATH: &str = "sinyalist_packets";
+
+// Merkle checkpoint log: signed `{seq, leaf_count, root_hex, signed_root}`
+// lines, one per bagged checkpoint — see `checkpoint_worker`.
+const CHECKPOINT_LOG_PATH: &str = "sinyalist_merkle_checkpoints.ndjson";
+const CHECKPOINT_INTERVAL_SECS: u64 = 30;
+
+// Gossip anti-entropy (C6): peers come from SINYALIST_GOSSIP_PEERS (comma
+// separated base URLs, e.g. "http://node-b:8080,http://node-c:8080"). Each
+// round a node pushes its changed clusters to a random GOSSIP_FANOUT peers
+// and pulls back what they have that it doesn't. The interval is also the
+// consensus dampening window: a remotely-accepted reporter becomes visible
+// to this node's consensus check within one round.
+const GOSSIP_FANOUT: usize = 3;
+const GOSSIP_INTERVAL_SECS: u64 = 5;
+
+/// C17: loads the shared secret `POST /v1/gossip/exchange` peers must
+/// present, from `SINYALIST_GOSSIP_SHARED_SECRET_HEX` (a 64-hex-char
+/// secret — same encoding as `trust::TrustPolicy`'s). `None` when unset,
+/// which leaves the endpoint open the way it was before this secret
+/// existed — fine for a single-operator deployment, not for gossiping
+/// across an untrusted network.
+fn gossip_secret_from_env() -> Option<[u8; 32]> {
+    match std::env::var("SINYALIST_GOSSIP_SHARED_SECRET_HEX").ok().and_then(|s| trust::decode_hex_32(s.trim())) {
+        Some(secret) => Some(secret),
+        None => {
+            if std::env::var("SINYALIST_GOSSIP_PEERS").map(|p| !p.trim().is_empty()).unwrap_or(false) {
+                warn!(
+                    "SINYALIST_GOSSIP_PEERS is set but SINYALIST_GOSSIP_SHARED_SECRET_HEX is missing/malformed — \
+                     /v1/gossip/exchange will accept unauthenticated deltas from any network-reachable caller"
+                );
+            }
+            None
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct AppState {
-    dedup: Arc<DashMap<Vec<u8>, u64>>,
-    persist_tx: mpsc::Sender<proto::SinyalistPacket>,
+    // C8: dedup has no per-entry update — an entry lives exactly DEDUP_TTL
+    // past its single insert, which is what TtlMap's expiry index is for.
+    dedup: Arc<TtlMap<Vec<u8>, ()>>,
+    persist_tx: mpsc::Sender<(String, proto::SinyalistPacket)>,
     afad_tx: mpsc::Sender<proto::SinyalistPacket>,
     m: Arc<Metrics>,
-    rl_key: Arc<DashMap<Vec<u8>, RateEntry>>,
-    rl_geo: Arc<DashMap<u64, RateEntry>>,
+    rl_key: Arc<TtlMap<Vec<u8>, RateEntry>>,
+    rl_geo: Arc<TtlMap<u64, RateEntry>>,
     clusters: Arc<DashMap<(u64,u64), GeoCluster>>,
-    known_keys: Arc<DashMap<Vec<u8>, u64>>,
+    // C11: first-seen time + confirmed-cluster history per key — the raw
+    // material `reputation::weight_of` turns into a consensus weight.
+    known_keys: Arc<DashMap<Vec<u8>, KeyReputation>>,
+    // Tamper-evident log over accepted packets (C5): `merkle` holds the MMR
+    // itself, `ingest_index` maps an ack's `ingest_id` to its leaf index so
+    // `GET /v1/proof` can find it once the persist worker has flushed it.
+    merkle: Arc<RwLock<MerkleLog>>,
+    ingest_index: Arc<DashMap<String, u64>>,
+    // C9: where flushed batches actually live — NDJSON by default, opt-in
+    // SQLite/LMDB for indexed queries. `GET /v1/query` reads through this.
+    backend: Arc<dyn PersistBackend>,
+    // C10: seals PII/medical fields before `flush()` hands a record to
+    // `backend`, and decrypts them back out for `GET /v1/query`.
+    keyring: Arc<KeyRing>,
+    // C12: fans out a `subscribe::ClusterUpdate` every time a cluster
+    // mutates — `GET /v1/subscribe` hands each connection its own receiver.
+    subscribe_hub: Arc<subscribe::Hub>,
+    // C15: which keys actually count as unique reporters toward
+    // `GeoCluster.keys`/`weighted_total` — see `trust::TrustPolicy`.
+    trust_policy: Arc<trust::TrustPolicy>,
+    // C17: shared secret `POST /v1/gossip/exchange` callers must present in
+    // `GOSSIP_SECRET_HEADER` — `None` (the default) leaves the endpoint open,
+    // matching `trust::TrustPolicy::Open`'s single-operator-deployment
+    // tradeoff. See `gossip_secret_from_env`.
+    gossip_secret: Option<[u8; 32]>,
+    // C16: fed to `hooks::hook_worker` every time a cluster first crosses
+    // consensus — see `GeoCluster.notified`.
+    hook_tx: mpsc::Sender<hooks::HookEvent>,
 }
 
 // C4: Full structured observability counters
@@ -155,6 +304,8 @@ pub struct Metrics {
     verify_fail: AtomicU64, spam: AtomicU64, malformed: AtomicU64, oversized: AtomicU64,
     accepted_ok: AtomicU64, processed_ok: AtomicU64, queue_full: AtomicU64,
     sig_missing: AtomicU64, timestamp_rejected: AtomicU64, consensus_pending: AtomicU64,
+    gossip_pushed: AtomicU64, gossip_received: AtomicU64, gossip_pulled: AtomicU64,
+    hooks_fired: AtomicU64, hooks_dropped: AtomicU64,
 }
 impl Metrics { fn new() -> Self { Self {
     ingested:AtomicU64::new(0), deduped:AtomicU64::new(0), afad:AtomicU64::new(0),
@@ -164,32 +315,26 @@ impl Metrics { fn new() -> Self { Self {
     accepted_ok:AtomicU64::new(0), processed_ok:AtomicU64::new(0),
     queue_full:AtomicU64::new(0), sig_missing:AtomicU64::new(0),
     timestamp_rejected:AtomicU64::new(0), consensus_pending:AtomicU64::new(0),
+    gossip_pushed:AtomicU64::new(0), gossip_received:AtomicU64::new(0), gossip_pulled:AtomicU64::new(0),
+    hooks_fired:AtomicU64::new(0), hooks_dropped:AtomicU64::new(0),
 }}}
 
-fn verify_sig(p: &proto::SinyalistPacket) -> bool {
-    if p.ed25519_public_key.len() != 32 || p.ed25519_signature.len() != 64 { return false; }
-    use ed25519_dalek::{Signature, VerifyingKey, Verifier};
-    // Sign the packet bytes WITHOUT the signature field
-    let mut sp = p.clone(); sp.ed25519_signature.clear();
-    let mut sb = Vec::with_capacity(sp.encoded_len());
-    if sp.encode(&mut sb).is_err() { return false; }
-    let Ok(pk) = <[u8;32]>::try_from(p.ed25519_public_key.as_slice()) else { return false; };
-    let Ok(sg) = <[u8;64]>::try_from(p.ed25519_signature.as_slice()) else { return false; };
-    let Ok(vk) = VerifyingKey::from_bytes(&pk) else { return false; };
-    let sig = Signature::from_bytes(&sg);
-    vk.verify(&sb, &sig).is_ok()
-}
-
-fn check_rl(m: &DashMap<Vec<u8>,RateEntry>, k: &[u8], now: u64, max: u32) -> bool {
-    let mut e = m.entry(k.to_vec()).or_insert(RateEntry{count:0,start_ms:now});
-    if now - e.start_ms > RL_WINDOW { e.count=1; e.start_ms=now; true }
-    else if e.count < max { e.count+=1; true } else { false }
+fn check_rl(m: &TtlMap<Vec<u8>, RateEntry>, k: &[u8], now: u64, max: u32) -> bool {
+    let mut allowed = false;
+    m.entry_and_bump(k.to_vec(), now, RL_IDLE_TTL, || RateEntry { count: 0, start_ms: now }, |e| {
+        allowed = if now - e.start_ms > RL_WINDOW { e.count = 1; e.start_ms = now; true }
+                  else if e.count < max { e.count += 1; true } else { false };
+    });
+    allowed
 }
 
-fn check_geo_rl(m: &DashMap<u64,RateEntry>, k: u64, now: u64) -> bool {
-    let mut e = m.entry(k).or_insert(RateEntry{count:0,start_ms:now});
-    if now - e.start_ms > RL_WINDOW { e.count=1; e.start_ms=now; true }
-    else if e.count < RL_PER_GEO { e.count+=1; true } else { false }
+fn check_geo_rl(m: &TtlMap<u64, RateEntry>, k: u64, now: u64) -> bool {
+    let mut allowed = false;
+    m.entry_and_bump(k, now, RL_IDLE_TTL, || RateEntry { count: 0, start_ms: now }, |e| {
+        allowed = if now - e.start_ms > RL_WINDOW { e.count = 1; e.start_ms = now; true }
+                  else if e.count < RL_PER_GEO { e.count += 1; true } else { false };
+    });
+    allowed
 }
 
 // Generate a unique ingest ID
@@ -201,8 +346,6 @@ fn generate_ingest_id() -> String {
 
 #[instrument(skip_all)]
 async fn ingest(State(s): State<AppState>, body: Bytes) -> impl IntoResponse {
-    let now = chrono::Utc::now().timestamp_millis() as u64;
-
     // C2: Strict size limit
     if body.len() > MAX_PKT {
         s.m.oversized.fetch_add(1, Ordering::Relaxed);
@@ -219,6 +362,119 @@ async fn ingest(State(s): State<AppState>, body: Bytes) -> impl IntoResponse {
         }
     };
 
+    process_packet(s, p, None).await
+}
+
+// C7: max base38 text length accepted over SMS — generous headroom over
+// MAX_PKT's worst-case 5-chars-per-3-bytes expansion (1024 bytes -> ~1710
+// chars), since SMS gateways sometimes concatenate multipart messages with
+// a little extra framing.
+const MAX_SMS_CHARS: usize = 2048;
+
+/// `POST /v1/ingest/sms` — same wire-level `SinyalistPacket`, base38-encoded
+/// to plain ASCII so it survives an SMS gateway (`ConnectivityMode::ConnSms`).
+/// Decodes to protobuf bytes and feeds the exact same pipeline as
+/// `/v1/ingest`, so signature verification, timestamp/dedup/consensus checks
+/// all apply identically — SMS is just a different wire encoding, not a
+/// different trust boundary. The ACK is returned as base38 text too, since
+/// an SMS reply can't carry raw protobuf bytes either.
+#[instrument(skip_all)]
+async fn ingest_sms(State(s): State<AppState>, text: String) -> impl IntoResponse {
+    let text = text.trim();
+    if text.len() > MAX_SMS_CHARS {
+        s.m.oversized.fetch_add(1, Ordering::Relaxed);
+        warn!(size=text.len(), max=MAX_SMS_CHARS, "oversized_sms_packet");
+        return (StatusCode::PAYLOAD_TOO_LARGE, HeaderMap::new(), String::new());
+    }
+
+    let Some(raw) = base38::decode(text) else {
+        s.m.malformed.fetch_add(1, Ordering::Relaxed);
+        warn!("sms_base38_decode_failed");
+        return (StatusCode::BAD_REQUEST, HeaderMap::new(), String::new());
+    };
+
+    // C2: Strict size limit — same bound as the protobuf endpoint, applied
+    // to the decoded bytes since that's what's actually being parsed.
+    if raw.len() > MAX_PKT {
+        s.m.oversized.fetch_add(1, Ordering::Relaxed);
+        warn!(size=raw.len(), max=MAX_PKT, "oversized_packet");
+        return (StatusCode::PAYLOAD_TOO_LARGE, HeaderMap::new(), String::new());
+    }
+
+    let p = match proto::SinyalistPacket::decode(Bytes::from(raw)) {
+        Ok(p) => p, Err(e) => {
+            s.m.malformed.fetch_add(1, Ordering::Relaxed);
+            warn!(error=%e, "malformed_packet");
+            return (StatusCode::BAD_REQUEST, HeaderMap::new(), String::new());
+        }
+    };
+
+    let (status, headers, ack) = process_packet(s, p, None).await;
+    (status, headers, base38::encode(&ack))
+}
+
+// C13: upper bound on packets per `/v1/ingest/batch` body — keeps a batch
+// sized for a burst from one mesh relay/SMS gateway, not an unbounded upload.
+const MAX_BATCH_PACKETS: usize = 256;
+
+/// `POST /v1/ingest/batch` — one or more `SinyalistPacket`s, each framed with
+/// prost's own length-delimited encoding (`Message::encode_length_delimited`),
+/// so a mesh relay or SMS gateway holding a burst of queued reports can flush
+/// them in one round trip. Signatures for the whole burst are checked
+/// together via `sigverify::verify_batch_parallel` before any packet reaches
+/// `process_packet`'s per-packet pipeline (dedup, rate limits, consensus,
+/// persistence all still run per packet). The response is the same
+/// length-delimited framing, one `PacketAck` per input packet in order —
+/// including a zero-length entry (decodes to `PacketAck::default()`, i.e.
+/// `received: false`) for whichever packets `process_packet` rejected before
+/// producing an ack body.
+#[instrument(skip_all)]
+async fn ingest_batch(State(s): State<AppState>, body: Bytes) -> impl IntoResponse {
+    let mut buf = body;
+    let mut packets = Vec::new();
+    while !buf.is_empty() {
+        if packets.len() >= MAX_BATCH_PACKETS {
+            s.m.oversized.fetch_add(1, Ordering::Relaxed);
+            warn!(count=packets.len(), max=MAX_BATCH_PACKETS, "oversized_batch");
+            return (StatusCode::PAYLOAD_TOO_LARGE, HeaderMap::new(), Bytes::new());
+        }
+        match proto::SinyalistPacket::decode_length_delimited(&mut buf) {
+            Ok(p) => packets.push(p),
+            Err(e) => {
+                s.m.malformed.fetch_add(1, Ordering::Relaxed);
+                warn!(error=%e, "malformed_batch_packet");
+                return (StatusCode::BAD_REQUEST, HeaderMap::new(), Bytes::new());
+            }
+        }
+    }
+
+    let now = chrono::Utc::now().timestamp_millis() as u64;
+    let sig_checks = sigverify::verify_batch_parallel(&packets, now);
+
+    let mut out = Vec::new();
+    for (p, check) in packets.into_iter().zip(sig_checks) {
+        let (_status, _headers, ack) = process_packet(s.clone(), p, Some(check)).await;
+        prost::encoding::encode_varint(ack.len() as u64, &mut out);
+        out.extend_from_slice(&ack);
+    }
+    (StatusCode::OK, HeaderMap::new(), Bytes::from(out))
+}
+
+/// The ingest pipeline shared by `/v1/ingest` (raw protobuf), `/v1/ingest/sms`
+/// (base38-decoded protobuf), and `/v1/ingest/batch` (C13): signature
+/// verification, timestamp window, dedup, rate limits, confidence/consensus
+/// scoring, AFAD routing, and persistence. Everything upstream of this —
+/// framing and decode — is transport-specific and handled by the caller.
+///
+/// `precomputed_sig_check`, when `Some`, skips the `verify_sig` call below in
+/// favor of a result `sigverify::verify_batch`/`verify_batch_parallel`
+/// already computed for the whole batch this packet arrived in —
+/// `SigCheck::DuplicatePacketId` is reported as a dedup drop rather than a
+/// signature failure. The required-fields and signature-presence checks
+/// still run per packet either way.
+async fn process_packet(s: AppState, p: proto::SinyalistPacket, precomputed_sig_check: Option<sigverify::SigCheck>) -> (StatusCode, HeaderMap, Bytes) {
+    let now = chrono::Utc::now().timestamp_millis() as u64;
+
     // C2: Required fields validation
     if p.user_id == 0 || p.timestamp_ms == 0 {
         s.m.malformed.fetch_add(1, Ordering::Relaxed);
@@ -226,20 +482,34 @@ async fn ingest(State(s): State<AppState>, body: Bytes) -> impl IntoResponse {
         return (StatusCode::UNPROCESSABLE_ENTITY, HeaderMap::new(), Bytes::new());
     }
 
-    // C2: Ed25519 signature REQUIRED (not optional)
-    // If signature is present, verify it. If missing, reject.
-    if p.ed25519_signature.is_empty() || p.ed25519_public_key.is_empty() {
+    // C2: Ed25519 signature REQUIRED (not optional) — either the packet's
+    // own `ed25519_signature`, or a Merkle-batch `merkle_signature` over a
+    // root it proves membership in (see `verify_sig` in lib.rs).
+    if p.ed25519_public_key.is_empty() || (p.ed25519_signature.is_empty() && p.merkle_signature.is_empty()) {
         s.m.sig_missing.fetch_add(1, Ordering::Relaxed);
         warn!(uid=p.user_id, "signature_missing");
         return (StatusCode::FORBIDDEN, HeaderMap::new(), Bytes::new());
     }
 
-    if !verify_sig(&p) {
+    if precomputed_sig_check == Some(sigverify::SigCheck::DuplicatePacketId) {
+        s.m.deduped.fetch_add(1, Ordering::Relaxed);
+        info!(uid=p.user_id, "dedup_drop_in_batch");
+        // Same "already accepted" response a cross-request dedup hit gets
+        // further down this pipeline — a within-batch packet_id repeat is a
+        // dedup drop, not a bad signature, and must not count against
+        // `verify_fail`.
+        return (StatusCode::OK, HeaderMap::new(), encode_ack(&p, true, 0.0, "already_accepted", &generate_ingest_id()));
+    }
+    let sig_valid = match precomputed_sig_check {
+        Some(check) => check.is_valid(),
+        None => verify_sig(&p),
+    };
+    if !sig_valid {
         s.m.verify_fail.fetch_add(1, Ordering::Relaxed);
         warn!(uid=p.user_id, "verify_fail");
         return (StatusCode::FORBIDDEN, HeaderMap::new(), Bytes::new());
     }
-    s.known_keys.entry(p.ed25519_public_key.clone()).or_insert(now);
+    s.known_keys.entry(p.ed25519_public_key.clone()).or_insert_with(|| KeyReputation::new(now));
 
     // Timestamp replay protection: reject packets that are too old or too far in the future.
     // created_at_ms is set by the device at packet creation time.
@@ -266,10 +536,12 @@ async fn ingest(State(s): State<AppState>, body: Bytes) -> impl IntoResponse {
     if s.dedup.contains_key(&dk) {
         s.m.deduped.fetch_add(1, Ordering::Relaxed);
         info!(uid=p.user_id, "dedup_drop");
-        // C1: Return 200 for dedup (already accepted), but don't inflate confidence
-        return (StatusCode::OK, HeaderMap::new(), encode_ack(&p, true, 0.0, "already_accepted"));
+        // C1: Return 200 for dedup (already accepted), but don't inflate confidence.
+        // Deduped packets were never (re-)persisted, so there's no fresh leaf
+        // to index — mint a throwaway ingest_id purely for the ACK body.
+        return (StatusCode::OK, HeaderMap::new(), encode_ack(&p, true, 0.0, "already_accepted", &generate_ingest_id()));
     }
-    s.dedup.insert(dk, now);
+    s.dedup.insert(dk, (), now, DEDUP_TTL);
 
     // Rate limits per public key
     if !check_rl(&s.rl_key, &p.ed25519_public_key, now, RL_PER_KEY) {
@@ -288,25 +560,89 @@ async fn ingest(State(s): State<AppState>, body: Bytes) -> impl IntoResponse {
 
     s.m.ingested.fetch_add(1, Ordering::Relaxed);
 
-    // C3: Confidence scoring — only unique public keys increase confidence
+    // C3/C11: Confidence scoring — weighted by reporter reputation, not raw
+    // unique-key count. A key only ever contributes its weight once (the
+    // first time it reports into this cluster), so duplicates still don't
+    // inflate confidence.
+    // C15: only a key `trust_policy` actually trusts counts toward
+    // `GeoCluster.keys`/`weighted_total` — an untrusted key's packet is still
+    // accepted and stored (below), just tracked separately so a Sybil flood
+    // of fresh-but-untrusted keys can't reach consensus.
+    let trusted = p.ed25519_public_key.len() == 32 && {
+        let mut ka = [0u8; 32];
+        ka.copy_from_slice(&p.ed25519_public_key);
+        s.trust_policy.is_trusted(&ka)
+    };
+
     let tb = time_bucket(p.timestamp_ms);
-    let (conf, unique_devices) = {
-        let mut c = s.clusters.entry((gk,tb)).or_insert_with(|| GeoCluster{keys:HashSet::new(),total:0,first_ms:now});
+    let mut hook_event: Option<hooks::HookEvent> = None;
+    let (conf, weighted_total, update) = {
+        let mut c = s.clusters.entry((gk,tb)).or_insert_with(|| GeoCluster{keys:HashSet::new(),untrusted_keys:HashSet::new(),total:0,first_ms:now,weighted_total:0.0,credited:false,notified:false,version:0,max_alert_level:0});
         c.total += 1;
         if p.ed25519_public_key.len() == 32 {
-            let mut ka = [0u8;32]; ka.copy_from_slice(&p.ed25519_public_key); c.keys.insert(ka);
+            let mut ka = [0u8;32]; ka.copy_from_slice(&p.ed25519_public_key);
+            if trusted {
+                if c.keys.insert(ka) {
+                    c.weighted_total += reputation::weight_of(&s.known_keys, &p.ed25519_public_key, now);
+                }
+            } else {
+                c.untrusted_keys.insert(ka);
+            }
         }
-        (c.confidence(), c.keys.len())
+        c.max_alert_level = c.max_alert_level.max(p.alert_level);
+        // C11: the first packet that pushes this cluster over the weight
+        // threshold credits every contributing key's `confirmed_clusters` —
+        // once per cluster, not once per packet, so a flood of packets into
+        // an already-confirmed cluster can't farm reputation.
+        if !c.credited && c.weighted_total >= CONSENSUS_WEIGHT_THRESHOLD {
+            c.credited = true;
+            for k in c.keys.iter() {
+                if let Some(mut rep) = s.known_keys.get_mut(k.as_slice()) {
+                    rep.confirmed_clusters = rep.confirmed_clusters.saturating_add(1);
+                }
+            }
+        }
+        // C16: same crossing as the `credited` check above, but gates the
+        // one-shot notification hook instead of reputation history — kept as
+        // its own flag so a future change to one doesn't silently retrigger
+        // (or silently suppress) the other.
+        if !c.notified && c.weighted_total >= CONSENSUS_WEIGHT_THRESHOLD {
+            c.notified = true;
+            let (lat_e7, lon_e7) = decode_geo_key(gk);
+            hook_event = Some(hooks::HookEvent {
+                geo_key: gk,
+                time_bucket: tb,
+                lat_e7,
+                lon_e7,
+                confidence: c.confidence(),
+                reporter_count: c.keys.len() as u32,
+                first_ms: c.first_ms,
+            });
+        }
+        // C12: every packet touching this cluster changes its confidence
+        // (at minimum `total`, which feeds the spam-factor penalty), so
+        // every packet bumps `version` and is worth pushing to subscribers.
+        c.version += 1;
+        (c.confidence(), c.weighted_total, subscribe::ClusterUpdate::from_cluster(gk, tb, &c))
     };
+    s.subscribe_hub.publish(update);
+    if let Some(ev) = hook_event {
+        if s.hook_tx.try_send(ev).is_err() {
+            s.m.hooks_dropped.fetch_add(1, Ordering::Relaxed);
+            warn!(geo=gk, "hook_queue_full — consensus notification dropped");
+        }
+    }
 
-    // Consensus check: if fewer than CONSENSUS_MIN_DEVICES unique devices have reported
-    // in this geo cell + time bucket, the packet is still accepted (stored, ACKed) but
-    // NOT forwarded to AFAD. This prevents a single malfunctioning device from triggering
-    // an alert. The confidence score returned to the client reflects the real cluster state.
-    let consensus_reached = unique_devices >= CONSENSUS_MIN_DEVICES;
+    // Consensus check: if summed reporter weight in this geo cell + time
+    // bucket is below CONSENSUS_WEIGHT_THRESHOLD, the packet is still
+    // accepted (stored, ACKed) but NOT forwarded to AFAD. This prevents a
+    // single malfunctioning device — or a Sybil flood of fresh keys — from
+    // triggering an alert. The confidence score returned to the client
+    // reflects the real cluster state.
+    let consensus_reached = weighted_total >= CONSENSUS_WEIGHT_THRESHOLD;
     if !consensus_reached {
         s.m.consensus_pending.fetch_add(1, Ordering::Relaxed);
-        info!(uid=p.user_id, unique_devices=unique_devices, needed=CONSENSUS_MIN_DEVICES,
+        info!(uid=p.user_id, weighted_total=weighted_total, needed=CONSENSUS_WEIGHT_THRESHOLD,
               "consensus_pending — packet accepted, AFAD relay withheld");
     }
 
@@ -318,12 +654,20 @@ async fn ingest(State(s): State<AppState>, body: Bytes) -> impl IntoResponse {
         let _ = s.afad_tx.try_send(p.clone());
     }
 
-    // C1: Persist — if queue is full, return 503 (honest backpressure)
-    match s.persist_tx.try_send(p.clone()) {
+    // C1/C5: Persist — if queue is full, return 503 (honest backpressure).
+    // The ingest_id is minted here, before the send, so the same ID the
+    // client sees in its ACK is the one `flush()` later indexes into the
+    // Merkle log — `GET /v1/proof?ingest_id=...` has something to look up.
+    let ingest_id = generate_ingest_id();
+    // C15: flag the ack when this packet's key was accepted but didn't count
+    // toward consensus, rather than letting "accepted" silently mean two
+    // different things depending on trust_policy.
+    let status = if trusted { "accepted" } else { "accepted_unverified" };
+    match s.persist_tx.try_send((ingest_id.clone(), p.clone())) {
         Ok(_) => {
             s.m.accepted_ok.fetch_add(1, Ordering::Relaxed);
-            info!(uid=p.user_id, trapped=p.is_trapped, conf=conf, "accepted_ok");
-            (StatusCode::OK, HeaderMap::new(), encode_ack(&p, true, conf, "accepted"))
+            info!(uid=p.user_id, trapped=p.is_trapped, conf=conf, trusted=trusted, "accepted_ok");
+            (StatusCode::OK, HeaderMap::new(), encode_ack(&p, true, conf, status, &ingest_id))
         }
         Err(mpsc::error::TrySendError::Full(_)) => {
             // C1: Queue full — do NOT pretend delivered
@@ -343,7 +687,7 @@ async fn ingest(State(s): State<AppState>, body: Bytes) -> impl IntoResponse {
     }
 }
 
-fn encode_ack(p: &proto::SinyalistPacket, ok: bool, conf: f32, status: &str) -> Bytes {
+fn encode_ack(p: &proto::SinyalistPacket, ok: bool, conf: f32, status: &str, ingest_id: &str) -> Bytes {
     let now_ms = chrono::Utc::now().timestamp_millis() as u64;
     let a = proto::PacketAck {
         user_id: p.user_id,
@@ -351,7 +695,7 @@ fn encode_ack(p: &proto::SinyalistPacket, ok: bool, conf: f32, status: &str) ->
         received: ok,
         rescue_eta: String::new(),
         confidence: conf,
-        ingest_id: generate_ingest_id(),
+        ingest_id: ingest_id.to_string(),
         status: status.to_string(),
     };
     let mut b = Vec::with_capacity(a.encoded_len());
@@ -386,14 +730,23 @@ struct MResp {
     timestamp_rejected: u64,
     // Consensus
     consensus_pending: u64,
-    consensus_min_devices: usize,
+    consensus_weight_threshold: f32,
     // Priority routing
     afad: u64,
     persisted: u64,
+    // Gossip anti-entropy (C6)
+    gossip_pushed: u64,
+    gossip_received: u64,
+    gossip_pulled: u64,
     // State sizes
     dedup_size: usize,
     keys: usize,
     clusters: usize,
+    // C12: connected `/v1/subscribe` dashboards
+    subscribers: usize,
+    // C16: consensus notification hooks
+    hooks_fired: u64,
+    hooks_dropped: u64,
 }
 
 async fn metrics(State(s): State<AppState>) -> Json<MResp> {
@@ -411,92 +764,347 @@ async fn metrics(State(s): State<AppState>) -> Json<MResp> {
         backpressure: s.m.backpressure.load(Ordering::Relaxed),
         timestamp_rejected: s.m.timestamp_rejected.load(Ordering::Relaxed),
         consensus_pending: s.m.consensus_pending.load(Ordering::Relaxed),
-        consensus_min_devices: CONSENSUS_MIN_DEVICES,
+        consensus_weight_threshold: CONSENSUS_WEIGHT_THRESHOLD,
         afad: s.m.afad.load(Ordering::Relaxed),
         persisted: s.m.persisted.load(Ordering::Relaxed),
+        gossip_pushed: s.m.gossip_pushed.load(Ordering::Relaxed),
+        gossip_received: s.m.gossip_received.load(Ordering::Relaxed),
+        gossip_pulled: s.m.gossip_pulled.load(Ordering::Relaxed),
         dedup_size: s.dedup.len(),
         keys: s.known_keys.len(),
         clusters: s.clusters.len(),
+        subscribers: s.subscribe_hub.subscriber_count(),
+        hooks_fired: s.m.hooks_fired.load(Ordering::Relaxed),
+        hooks_dropped: s.m.hooks_dropped.load(Ordering::Relaxed),
     };
     Json(r)
 }
 
-async fn eviction(d: Arc<DashMap<Vec<u8>,u64>>, c: Arc<DashMap<(u64,u64),GeoCluster>>,
-                  rk: Arc<DashMap<Vec<u8>,RateEntry>>, rg: Arc<DashMap<u64,RateEntry>>) {
+#[derive(Deserialize)]
+struct ProofQuery {
+    ingest_id: String,
+}
+
+#[derive(Serialize)]
+struct ProofStepJson {
+    hash_hex: String,
+    left: bool,
+}
+
+#[derive(Serialize)]
+struct ProofResp {
+    ingest_id: String,
+    leaf_index: u64,
+    leaf_count: u64,
+    root_hex: String,
+    path: Vec<ProofStepJson>,
+}
+
+impl ProofResp {
+    fn new(ingest_id: String, p: merkle::InclusionProof) -> Self {
+        ProofResp {
+            ingest_id,
+            leaf_index: p.leaf_index,
+            leaf_count: p.leaf_count,
+            root_hex: hex_encode(&p.root),
+            path: p.path.into_iter().map(|s| ProofStepJson { hash_hex: hex_encode(&s.hash), left: s.left }).collect(),
+        }
+    }
+}
+
+// C5: GET /v1/proof?ingest_id=... — Merkle inclusion proof for an accepted
+// packet, so AFAD or an external auditor can verify for themselves that the
+// server didn't silently drop or alter it after ACKing. 404 covers both an
+// unknown ID (never accepted, or deduped — dedup never mints a fresh leaf)
+// and one not yet flushed to the log by the persist worker.
+async fn proof(State(s): State<AppState>, Query(q): Query<ProofQuery>) -> impl IntoResponse {
+    let Some(leaf_index) = s.ingest_index.get(&q.ingest_id).map(|e| *e.value()) else {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "unknown ingest_id"}))).into_response();
+    };
+    let Some(p) = s.merkle.read().unwrap().prove(leaf_index) else {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "leaf index no longer in log"}))).into_response();
+    };
+    (StatusCode::OK, Json(ProofResp::new(q.ingest_id, p))).into_response()
+}
+
+// C6/C17: POST /v1/gossip/exchange — anti-entropy push/pull. The caller's
+// `push` deltas are merged into `clusters` immediately (conflict-free union,
+// see gossip::merge_delta), and the response carries back whatever this
+// node has that the caller's `known` filter says it doesn't.
+//
+// C17: gated behind `GOSSIP_SECRET_HEADER` when `s.gossip_secret` is
+// configured — without it, any network-reachable caller could forge a
+// `GossipDelta` and have it merged straight into live consensus state (and
+// re-gossiped onward to every other peer from there). See
+// `main::gossip_secret_from_env` / `trust::TrustPolicy` (the same
+// shared-secret idea, but authenticating the *peer node*, not a packet's
+// signing key).
+const GOSSIP_SECRET_HEADER: &str = "x-gossip-secret";
+
+async fn gossip_exchange(
+    State(s): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<gossip::GossipExchange>,
+) -> impl IntoResponse {
+    if let Some(expected) = &s.gossip_secret {
+        let presented = headers.get(GOSSIP_SECRET_HEADER).and_then(|v| v.to_str().ok()).and_then(trust::decode_hex_32);
+        // Constant-time: this secret exists to authenticate gossip peers, so
+        // comparing it with a short-circuiting `==`/`!=` would leak which
+        // byte first differs via timing.
+        let authenticated = presented.as_ref().is_some_and(|p| trust::constant_time_eq(p, expected));
+        if !authenticated {
+            warn!("gossip_exchange_rejected_unauthenticated");
+            return (StatusCode::UNAUTHORIZED, Json(gossip::GossipExchangeResp { deltas: vec![] }));
+        }
+    }
+    let now = chrono::Utc::now().timestamp_millis() as u64;
+    for d in &body.push {
+        if let Some(update) = gossip::merge_delta(&s.clusters, &s.known_keys, &s.trust_policy, d, now) {
+            s.subscribe_hub.publish(update);
+        }
+    }
+    s.m.gossip_received.fetch_add(body.push.len() as u64, Ordering::Relaxed);
+
+    let deltas = gossip::diff_against(&s.clusters, &s.known_keys, &body.known);
+    s.m.gossip_pulled.fetch_add(deltas.len() as u64, Ordering::Relaxed);
+    (StatusCode::OK, Json(gossip::GossipExchangeResp { deltas }))
+}
+
+#[derive(Deserialize)]
+struct QueryParams {
+    geo_key: Option<u64>,
+    time_bucket: Option<u64>,
+    start_ms: Option<u64>,
+    end_ms: Option<u64>,
+    trapped_since_ms: Option<u64>,
+}
+
+// C10: the JSON shape `GET /v1/query` actually returns — the plaintext
+// index fields straight off `PacketRecord`, plus whatever of `Payload`
+// decrypted cleanly. A record whose sealing key has since rotated out (or
+// whose ciphertext fails to authenticate) still shows up with `payload:
+// null` rather than vanishing or 500ing the whole response.
+#[derive(Serialize)]
+struct QueryRecordView {
+    packet_id_hex: String,
+    geo_key: u64,
+    time_bucket: u64,
+    timestamp_ms: u64,
+    is_trapped: bool,
+    alert_level: i32,
+    payload: Option<Payload>,
+}
+
+impl QueryRecordView {
+    fn new(rec: &PacketRecord, keyring: &KeyRing) -> Self {
+        Self {
+            packet_id_hex: rec.packet_id_hex.clone(),
+            geo_key: rec.geo_key,
+            time_bucket: rec.time_bucket,
+            timestamp_ms: rec.timestamp_ms,
+            is_trapped: rec.is_trapped,
+            alert_level: rec.alert_level,
+            payload: rec.open(keyring),
+        }
+    }
+}
+
+// C9: GET /v1/query — operator tooling reads persisted packets through
+// whichever `PersistBackend` is active instead of grepping the NDJSON file
+// by hand. Exactly one of the three filter shapes a `PersistBackend` method
+// supports must be given; anything else is a 400, not a silent empty scan.
+// C10: PII/medical fields come back transparently decrypted — the backend
+// only ever stored the sealed envelope.
+async fn query(State(s): State<AppState>, Query(q): Query<QueryParams>) -> impl IntoResponse {
+    let records = if let (Some(g), Some(t)) = (q.geo_key, q.time_bucket) {
+        s.backend.by_geo_cell(g, t)
+    } else if let Some(since) = q.trapped_since_ms {
+        s.backend.trapped_since(since)
+    } else if let (Some(start), Some(end)) = (q.start_ms, q.end_ms) {
+        s.backend.by_time_range(start, end)
+    } else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "specify geo_key+time_bucket, start_ms+end_ms, or trapped_since_ms"})),
+        )
+            .into_response();
+    };
+    let views: Vec<QueryRecordView> = records.iter().map(|r| QueryRecordView::new(r, &s.keyring)).collect();
+    (StatusCode::OK, Json(views)).into_response()
+}
+
+#[derive(Deserialize)]
+struct ReportersQuery {
+    geo_key: u64,
+    time_bucket: u64,
+    n: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct ReporterView {
+    key_hex: String,
+    weight: f32,
+}
+
+// C17: GET /v1/reporters — the top `n` (default 5) trusted reporters of a
+// cluster, ordered by `weighted_shuffle` instead of `keys`' arbitrary
+// `HashSet` iteration order, so "representative reporters" actually leans
+// toward the cluster's most-reputable keys rather than whichever happened
+// to hash first.
+async fn reporters(State(s): State<AppState>, Query(q): Query<ReportersQuery>) -> impl IntoResponse {
+    let Some(c) = s.clusters.get(&(q.geo_key, q.time_bucket)) else {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "unknown cluster"}))).into_response();
+    };
+    let now = chrono::Utc::now().timestamp_millis() as u64;
+    let keys: Vec<[u8; 32]> = c.keys.iter().copied().collect();
+    let weights: Vec<f32> = keys.iter().map(|k| reputation::weight_of(&s.known_keys, k.as_slice(), now)).collect();
+    let n = q.n.unwrap_or(5).min(keys.len());
+    let seed = weighted_shuffle::cluster_seed(q.geo_key, q.time_bucket);
+    let order = weighted_shuffle::weighted_shuffle(&weights, seed);
+    let top: Vec<ReporterView> = order
+        .into_iter()
+        .take(n)
+        .map(|i| ReporterView { key_hex: hex_encode(&keys[i]), weight: weights[i] })
+        .collect();
+    (StatusCode::OK, Json(top)).into_response()
+}
+
+// C12: GET /v1/subscribe — opens an SSE stream: a snapshot of every
+// currently-matching cluster, then one `cluster_update` event per
+// subsequent change, filtered by the same query the snapshot used. Query
+// params double as both the snapshot filter and the live filter so a
+// client never sees a cluster in the snapshot that its own live filter
+// would then silently drop.
+async fn subscribe(State(s): State<AppState>, Query(q): Query<subscribe::SubscribeQuery>) -> impl IntoResponse {
+    let rx = s.subscribe_hub.subscribe();
+    let snapshot: Vec<subscribe::ClusterUpdate> = s
+        .clusters
+        .iter()
+        .map(|e| subscribe::ClusterUpdate::from_cluster(e.key().0, e.key().1, e.value()))
+        .filter(|u| q.matches(u))
+        .collect();
+    axum::response::sse::Sse::new(subscribe::event_stream(snapshot, rx, q))
+        .keep_alive(axum::response::sse::KeepAlive::default())
+}
+
+// C8: clusters aren't swapped onto TtlMap — their TTL check mixes
+// `CONSENSUS_WEIGHT_THRESHOLD` bookkeeping with confidence scoring read
+// directly off the DashMap from several places (gossip merge/diff, ingest),
+// so this keeps the simple fixed-interval sweep.
+async fn cluster_eviction(c: Arc<DashMap<(u64,u64),GeoCluster>>) {
     let mut iv = tokio::time::interval(Duration::from_secs(60));
-    loop { iv.tick().await;
+    loop {
+        iv.tick().await;
         let now = chrono::Utc::now().timestamp_millis() as u64;
-        let d_before = d.len();
-        d.retain(|_,&mut ts| now.saturating_sub(ts) < DEDUP_TTL);
         c.retain(|_,cl| now.saturating_sub(cl.first_ms) < 300_000);
-        rk.retain(|_,e| now.saturating_sub(e.start_ms) < RL_WINDOW*2);
-        rg.retain(|_,e| now.saturating_sub(e.start_ms) < RL_WINDOW*2);
-        let d_after = d.len();
-        if d_before != d_after {
-            info!(evicted=d_before-d_after, remaining=d_after, "dedup_eviction");
+    }
+}
+
+/// Background driver for `dedup`/`rl_key`/`rl_geo`: sleeps until the
+/// earliest pending expiry across all three, then evicts exactly what's
+/// aged out — O(expired) per wakeup instead of O(total) per fixed tick, and
+/// an entry is reclaimed within milliseconds of its TTL instead of up to a
+/// minute late.
+async fn ttl_eviction(dedup: Arc<TtlMap<Vec<u8>, ()>>, rl_key: Arc<TtlMap<Vec<u8>, RateEntry>>, rl_geo: Arc<TtlMap<u64, RateEntry>>) {
+    loop {
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let next = [dedup.next_expiry(), rl_key.next_expiry(), rl_geo.next_expiry()].into_iter().flatten().min();
+        let sleep_for = match next {
+            Some(exp) => Duration::from_millis(exp.saturating_sub(now)),
+            // Nothing queued yet (e.g. just after startup) — check back soon
+            // rather than sleeping indefinitely.
+            None => Duration::from_secs(5),
+        };
+        tokio::time::sleep(sleep_for).await;
+
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let d_evicted = dedup.evict_expired(now).len();
+        let rk_evicted = rl_key.evict_expired(now).len();
+        let rg_evicted = rl_geo.evict_expired(now).len();
+        if d_evicted > 0 {
+            info!(evicted=d_evicted, remaining=dedup.len(), "dedup_eviction");
+        }
+        if rk_evicted > 0 || rg_evicted > 0 {
+            info!(rl_key_evicted=rk_evicted, rl_geo_evicted=rg_evicted, "rate_limit_eviction");
         }
     }
 }
 
-async fn persist_worker(mut rx: mpsc::Receiver<proto::SinyalistPacket>, m: Arc<Metrics>) {
+async fn persist_worker(
+    mut rx: mpsc::Receiver<(String, proto::SinyalistPacket)>,
+    m: Arc<Metrics>,
+    merkle: Arc<RwLock<MerkleLog>>,
+    ingest_index: Arc<DashMap<String, u64>>,
+    backend: Arc<dyn PersistBackend>,
+    keyring: Arc<KeyRing>,
+) {
     let mut batch = Vec::with_capacity(1000);
     let mut iv = tokio::time::interval(Duration::from_millis(100));
     loop {
         tokio::select! {
-            Some(p) = rx.recv() => { batch.push(p); if batch.len()>=1000 { flush(&mut batch,&m).await; } }
-            _ = iv.tick() => { if !batch.is_empty() { flush(&mut batch,&m).await; } }
+            Some(item) = rx.recv() => { batch.push(item); if batch.len()>=1000 { flush(&mut batch,&m,&merkle,&ingest_index,&backend,&keyring).await; } }
+            _ = iv.tick() => { if !batch.is_empty() { flush(&mut batch,&m,&merkle,&ingest_index,&backend,&keyring).await; } }
         }
     }
 }
 
-/// Packet record serialized to NDJSON for basic persistence.
-#[derive(Serialize, Deserialize)]
-struct PacketRecord {
-    user_id: u64,
-    lat_e7: i32,
-    lon_e7: i32,
-    timestamp_ms: u64,
-    created_at_ms: u64,
-    is_trapped: bool,
-    msg_type: i32,
-    alert_level: i32,
-    pubkey_hex: String,
-    packet_id_hex: String,
-}
-
-async fn flush(b: &mut Vec<proto::SinyalistPacket>, m: &Metrics) {
+async fn flush(
+    b: &mut Vec<(String, proto::SinyalistPacket)>,
+    m: &Metrics,
+    merkle: &RwLock<MerkleLog>,
+    ingest_index: &DashMap<String, u64>,
+    backend: &Arc<dyn PersistBackend>,
+    keyring: &KeyRing,
+) {
     let n = b.len();
-    let t = b.iter().filter(|p| p.is_trapped).count();
+    let t = b.iter().filter(|(_, p)| p.is_trapped).count();
     info!(packets=n, trapped=t, "batch_flush");
 
-    // Append NDJSON records to persist log — survives server restarts.
-    // This is not a full database but prevents total data loss on crash.
-    match tokio::fs::OpenOptions::new()
-        .create(true).append(true)
-        .open(PERSIST_LOG_PATH).await
-    {
-        Ok(mut f) => {
-            for p in b.iter() {
-                let rec = PacketRecord {
-                    user_id: p.user_id,
-                    lat_e7: p.latitude_e7,
-                    lon_e7: p.longitude_e7,
-                    timestamp_ms: p.timestamp_ms,
-                    created_at_ms: p.created_at_ms,
-                    is_trapped: p.is_trapped,
-                    msg_type: p.msg_type,
-                    alert_level: p.alert_level,
-                    pubkey_hex: hex_encode(&p.ed25519_public_key),
-                    packet_id_hex: hex_encode(&p.packet_id),
-                };
-                if let Ok(line) = serde_json::to_string(&rec) {
-                    let _ = f.write_all(format!("{}\n", line).as_bytes()).await;
-                }
-            }
-        }
-        Err(e) => {
-            error!("persist_log_open_failed: {}", e);
+    let mut records = Vec::with_capacity(n);
+    for (ingest_id, p) in b.iter() {
+        let packet_id_hex = hex_encode(&p.packet_id);
+        // C10: PII/medical fields are sealed here, before the record ever
+        // reaches a `PersistBackend` — `packet_id_hex` is bound as AAD so
+        // the ciphertext only authenticates against this one packet.
+        let payload = Payload {
+            user_id: p.user_id,
+            lat_e7: p.latitude_e7,
+            lon_e7: p.longitude_e7,
+            created_at_ms: p.created_at_ms,
+            msg_type: p.msg_type,
+            pubkey_hex: hex_encode(&p.ed25519_public_key),
+            room_hint: p.room_hint.clone(),
+            blood_type: p.blood_type,
+            pulse_bpm: p.pulse_bpm,
+            spo2_percent: p.spo2_percent,
+        };
+        let rec = PacketRecord {
+            sealed: keyring.seal(&payload, packet_id_hex.as_bytes()),
+            packet_id_hex,
+            geo_key: geo_key(p.latitude_e7, p.longitude_e7),
+            time_bucket: time_bucket(p.timestamp_ms),
+            timestamp_ms: p.timestamp_ms,
+            is_trapped: p.is_trapped,
+            alert_level: p.alert_level,
+        };
+        if let Ok(line) = serde_json::to_string(&rec) {
+            // C5: the Merkle leaf is SHA-256 over the exact canonical JSON
+            // bytes below — independent of which `PersistBackend` actually
+            // stores the record, so the published root keeps committing to
+            // the same bytes an auditor re-hashes, regardless of backend.
+            // C10: that now includes the sealed payload envelope, not the
+            // plaintext fields — the log commits to exactly what's on disk.
+            let leaf_index = merkle.write().unwrap().append(line.as_bytes());
+            ingest_index.insert(ingest_id.clone(), leaf_index);
         }
+        records.push(rec);
+    }
+
+    // C9: the backend owns its own storage+indexing; `flush` just hands it
+    // the batch. Same blocking-call-inside-async-fn tradeoff `merkle`'s
+    // `RwLock` already makes here — fine for a 100ms/1000-record tick.
+    if let Err(e) = backend.append(&records) {
+        error!("persist_backend_append_failed: {}", e);
     }
 
     m.persisted.fetch_add(n as u64, Ordering::Relaxed);
@@ -508,6 +1116,179 @@ fn hex_encode(b: &[u8]) -> String {
     b.iter().map(|x| format!("{:02x}", x)).collect()
 }
 
+/// Loads the server's Merkle checkpoint signing key from
+/// `SINYALIST_LOG_SIGNING_KEY_HEX` (64 hex chars = a 32-byte seed), or
+/// generates an ephemeral one. An ephemeral key still lets a single run's
+/// checkpoints be verified against each other, but can't be pinned by an
+/// auditor across restarts — set the env var for that.
+fn load_or_generate_log_signing_key() -> SigningKey {
+    if let Ok(hex_seed) = std::env::var("SINYALIST_LOG_SIGNING_KEY_HEX") {
+        match decode_hex_32(&hex_seed) {
+            Some(seed) => return SigningKey::from_bytes(&seed),
+            None => warn!("SINYALIST_LOG_SIGNING_KEY_HEX is set but is not 64 hex chars; generating an ephemeral key instead"),
+        }
+    }
+    warn!("no SINYALIST_LOG_SIGNING_KEY_HEX set — generating an ephemeral Merkle checkpoint signing key; checkpoints won't verify against a known key across restarts");
+    SigningKey::generate(&mut rand::rngs::OsRng)
+}
+
+fn decode_hex_32(s: &str) -> Option<[u8; 32]> {
+    if s.len() != 64 {
+        return None;
+    }
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(out)
+}
+
+#[derive(Serialize)]
+struct Checkpoint {
+    seq: u64,
+    leaf_count: u64,
+    root_hex: String,
+    signed_root: String,
+}
+
+/// Periodically bags the Merkle log's current peaks into a root, signs it
+/// with the server's Ed25519 checkpoint key, and appends a checkpoint line
+/// to `CHECKPOINT_LOG_PATH`. A checkpoint commits to the exact multiset of
+/// packets accepted so far — anyone holding `signed_root` can detect the
+/// server silently dropping or altering an already-ACKed packet.
+async fn checkpoint_worker(merkle: Arc<RwLock<MerkleLog>>, signing_key: Arc<SigningKey>) {
+    let mut iv = tokio::time::interval(Duration::from_secs(CHECKPOINT_INTERVAL_SECS));
+    let mut seq = 0u64;
+    let mut last_leaf_count = 0u64;
+    loop {
+        iv.tick().await;
+        let (leaf_count, root) = {
+            let m = merkle.read().unwrap();
+            (m.leaf_count(), m.root())
+        };
+        if leaf_count == last_leaf_count {
+            continue; // nothing new accepted since the last checkpoint
+        }
+        last_leaf_count = leaf_count;
+
+        let signature = signing_key.sign(&root);
+        let cp = Checkpoint {
+            seq,
+            leaf_count,
+            root_hex: hex_encode(&root),
+            signed_root: hex_encode(&signature.to_bytes()),
+        };
+        seq += 1;
+
+        match tokio::fs::OpenOptions::new().create(true).append(true).open(CHECKPOINT_LOG_PATH).await {
+            Ok(mut f) => {
+                if let Ok(line) = serde_json::to_string(&cp) {
+                    let _ = f.write_all(format!("{}\n", line).as_bytes()).await;
+                    info!(seq=cp.seq, leaf_count=cp.leaf_count, root=%cp.root_hex, "merkle_checkpoint");
+                }
+            }
+            Err(e) => error!("checkpoint_log_open_failed: {}", e),
+        }
+    }
+}
+
+/// Periodic anti-entropy round: push this node's changed clusters to a
+/// random `GOSSIP_FANOUT` peers and merge back whatever they have that we
+/// don't. Disabled (logs once, then returns) when `SINYALIST_GOSSIP_PEERS`
+/// is unset, so a single-node deployment pays no cost for this. Presents
+/// `gossip_secret` (if configured) in `GOSSIP_SECRET_HEADER` on every push
+/// so peers running with a configured secret of their own accept it (C17).
+async fn gossip_worker(clusters: Arc<DashMap<(u64, u64), GeoCluster>>, known_keys: Arc<DashMap<Vec<u8>, KeyReputation>>, trust_policy: Arc<trust::TrustPolicy>, gossip_secret: Option<[u8; 32]>, m: Arc<Metrics>, subscribe_hub: Arc<subscribe::Hub>) {
+    let peers: Vec<String> = std::env::var("SINYALIST_GOSSIP_PEERS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if peers.is_empty() {
+        info!("SINYALIST_GOSSIP_PEERS not set — gossip disabled, single-node consensus only");
+        return;
+    }
+    info!(peers=?peers, fanout=GOSSIP_FANOUT, "gossip_enabled");
+
+    let client = match reqwest::Client::builder().timeout(Duration::from_secs(3)).build() {
+        Ok(c) => c,
+        Err(e) => { error!("gossip_client_build_failed: {}", e); return; }
+    };
+    // What we last told peers our `total` was, per cluster — so a round only
+    // pushes clusters that changed since the last one instead of re-sending
+    // a static cluster's full key set forever.
+    let mut last_sent: std::collections::HashMap<(u64, u64), u64> = std::collections::HashMap::new();
+    let mut iv = tokio::time::interval(Duration::from_secs(GOSSIP_INTERVAL_SECS));
+
+    loop {
+        iv.tick().await;
+
+        let push: Vec<gossip::GossipDelta> = clusters
+            .iter()
+            .filter(|e| last_sent.get(e.key()).copied().unwrap_or(0) < e.value().total)
+            .map(|e| gossip::GossipDelta {
+                geo_key: e.key().0,
+                time_bucket: e.key().1,
+                keys: e.value().keys.iter().map(gossip::encode_key).collect(),
+                untrusted_keys: e.value().untrusted_keys.iter().map(gossip::encode_key).collect(),
+                total: e.value().total,
+                first_ms: e.value().first_ms,
+                reps: e
+                    .value()
+                    .keys
+                    .iter()
+                    .filter_map(|k| {
+                        known_keys.get(k.as_slice()).map(|r| gossip::GossipKeyRep {
+                            key_hex: gossip::encode_key(k),
+                            first_seen_ms: r.first_seen_ms,
+                            confirmed_clusters: r.confirmed_clusters,
+                        })
+                    })
+                    .collect(),
+            })
+            .collect();
+        for d in &push {
+            last_sent.insert((d.geo_key, d.time_bucket), d.total);
+        }
+        let known: Vec<(u64, u64, u64)> = clusters.iter().map(|e| (e.key().0, e.key().1, e.value().total)).collect();
+
+        // `choose_multiple` borrows a `ThreadRng`, which isn't `Send` — collect
+        // the chosen peers into an owned Vec before any `.await` below so the
+        // rng itself never needs to live across a suspension point.
+        let fanout: Vec<String> = peers
+            .choose_multiple(&mut rand::thread_rng(), GOSSIP_FANOUT.min(peers.len()))
+            .cloned()
+            .collect();
+        for peer in &fanout {
+            let url = format!("{}/v1/gossip/exchange", peer.trim_end_matches('/'));
+            let body = gossip::GossipExchange { push: push.clone(), known: known.clone() };
+            let mut req = client.post(&url).json(&body);
+            if let Some(secret) = &gossip_secret {
+                req = req.header(GOSSIP_SECRET_HEADER, gossip::encode_key(secret));
+            }
+            match req.send().await {
+                Ok(resp) if resp.status().is_success() => {
+                    m.gossip_pushed.fetch_add(push.len() as u64, Ordering::Relaxed);
+                    match resp.json::<gossip::GossipExchangeResp>().await {
+                        Ok(reply) => {
+                            let now = chrono::Utc::now().timestamp_millis() as u64;
+                            for d in &reply.deltas {
+                                if let Some(update) = gossip::merge_delta(&clusters, &known_keys, &trust_policy, d, now) {
+                                    subscribe_hub.publish(update);
+                                }
+                            }
+                        }
+                        Err(e) => warn!(peer=%peer, error=%e, "gossip_reply_decode_failed"),
+                    }
+                }
+                Ok(resp) => warn!(peer=%peer, status=%resp.status(), "gossip_exchange_rejected"),
+                Err(e) => warn!(peer=%peer, error=%e, "gossip_exchange_failed"),
+            }
+        }
+    }
+}
+
 async fn afad_worker(mut rx: mpsc::Receiver<proto::SinyalistPacket>) {
     while let Some(p) = rx.recv().await {
         info!(uid=p.user_id, lat=p.latitude_e7 as f64/1e7, lon=p.longitude_e7 as f64/1e7,
@@ -524,22 +1305,47 @@ async fn main() {
 
     let (ptx, prx) = mpsc::channel(100_000);
     let (atx, arx) = mpsc::channel(10_000);
+    let (htx, hrx) = mpsc::channel(1_000);
     let m = Arc::new(Metrics::new());
+    let merkle = Arc::new(RwLock::new(MerkleLog::new()));
+    let ingest_index = Arc::new(DashMap::with_capacity(100_000));
+    let log_signing_key = Arc::new(load_or_generate_log_signing_key());
+    let backend: Arc<dyn PersistBackend> = Arc::from(storage::build_backend(PERSIST_BASE_PATH));
+    let keyring = Arc::new(KeyRing::load_or_generate());
     let s = AppState {
-        dedup: Arc::new(DashMap::with_capacity(500_000)), persist_tx:ptx, afad_tx:atx, m:m.clone(),
-        rl_key: Arc::new(DashMap::with_capacity(10_000)),
-        rl_geo: Arc::new(DashMap::with_capacity(1_000)),
+        dedup: Arc::new(TtlMap::with_capacity(500_000)), persist_tx:ptx, afad_tx:atx, m:m.clone(),
+        rl_key: Arc::new(TtlMap::with_capacity(10_000)),
+        rl_geo: Arc::new(TtlMap::with_capacity(1_000)),
         clusters: Arc::new(DashMap::with_capacity(10_000)),
         known_keys: Arc::new(DashMap::with_capacity(100_000)),
+        merkle: merkle.clone(),
+        ingest_index: ingest_index.clone(),
+        backend: backend.clone(),
+        keyring: keyring.clone(),
+        subscribe_hub: Arc::new(subscribe::Hub::new()),
+        trust_policy: Arc::new(trust::TrustPolicy::from_env()),
+        gossip_secret: gossip_secret_from_env(),
+        hook_tx: htx,
     };
 
-    tokio::spawn(eviction(s.dedup.clone(), s.clusters.clone(), s.rl_key.clone(), s.rl_geo.clone()));
-    tokio::spawn(persist_worker(prx, m.clone()));
+    tokio::spawn(cluster_eviction(s.clusters.clone()));
+    tokio::spawn(ttl_eviction(s.dedup.clone(), s.rl_key.clone(), s.rl_geo.clone()));
+    tokio::spawn(persist_worker(prx, m.clone(), merkle.clone(), ingest_index.clone(), backend.clone(), keyring.clone()));
+    tokio::spawn(checkpoint_worker(merkle.clone(), log_signing_key.clone()));
+    tokio::spawn(gossip_worker(s.clusters.clone(), s.known_keys.clone(), s.trust_policy.clone(), s.gossip_secret, m.clone(), s.subscribe_hub.clone()));
     tokio::spawn(afad_worker(arx));
+    tokio::spawn(hooks::hook_worker(hrx, m.clone()));
 
     let port: u16 = std::env::var("PORT").ok().and_then(|p|p.parse().ok()).unwrap_or(8080);
     let app = Router::new()
         .route("/v1/ingest", post(ingest))
+        .route("/v1/ingest/sms", post(ingest_sms))
+        .route("/v1/ingest/batch", post(ingest_batch))
+        .route("/v1/proof", get(proof))
+        .route("/v1/gossip/exchange", post(gossip_exchange))
+        .route("/v1/query", get(query))
+        .route("/v1/reporters", get(reporters))
+        .route("/v1/subscribe", get(subscribe))
         .route("/health", get(health))
         .route("/ready", get(ready))
         .route("/metrics", get(metrics))
@@ -596,7 +1402,9 @@ mod tests {
 
     #[test]
     fn test_confidence_single_reporter() {
-        let mut c = GeoCluster { keys: HashSet::new(), total: 1, first_ms: 0 };
+        // A single fresh key weighs exactly `reputation::BASE_WEIGHT` (1.0),
+        // so this matches the old flat-count single-reporter case.
+        let mut c = GeoCluster { keys: HashSet::new(), untrusted_keys: HashSet::new(), total: 1, first_ms: 0, weighted_total: 1.0, credited: false, notified: false, version: 0, max_alert_level: 0 };
         c.keys.insert([1u8; 32]);
         let conf = c.confidence();
         // ln(1) + 1 = 1.0, / 3.0 = 0.333
@@ -605,7 +1413,7 @@ mod tests {
 
     #[test]
     fn test_confidence_three_reporters() {
-        let mut c = GeoCluster { keys: HashSet::new(), total: 3, first_ms: 0 };
+        let mut c = GeoCluster { keys: HashSet::new(), untrusted_keys: HashSet::new(), total: 3, first_ms: 0, weighted_total: 3.0, credited: false, notified: false, version: 0, max_alert_level: 0 };
         c.keys.insert([1u8; 32]);
         c.keys.insert([2u8; 32]);
         c.keys.insert([3u8; 32]);
@@ -617,17 +1425,18 @@ mod tests {
     #[test]
     fn test_confidence_duplicates_dont_inflate() {
         // C3: Same public key sending 10 times should NOT inflate confidence
-        let mut c = GeoCluster { keys: HashSet::new(), total: 10, first_ms: 0 };
-        c.keys.insert([1u8; 32]); // Only 1 unique key despite 10 total
+        // — only 1 unique key's weight is ever credited despite 10 total.
+        let mut c = GeoCluster { keys: HashSet::new(), untrusted_keys: HashSet::new(), total: 10, first_ms: 0, weighted_total: 1.0, credited: false, notified: false, version: 0, max_alert_level: 0 };
+        c.keys.insert([1u8; 32]);
         let conf = c.confidence();
-        // total(10) > unique(1) * 3 → spam factor 0.5
+        // total(10) > weight(1) * 3 → spam factor 0.5
         // ln(1) + 1 = 1.0, / 3.0 * 0.5 = 0.167
         assert!(conf < 0.2, "Duplicate spam should not inflate confidence, got {}", conf);
     }
 
     #[test]
     fn test_confidence_capped_at_one() {
-        let mut c = GeoCluster { keys: HashSet::new(), total: 20, first_ms: 0 };
+        let mut c = GeoCluster { keys: HashSet::new(), untrusted_keys: HashSet::new(), total: 20, first_ms: 0, weighted_total: 20.0, credited: false, notified: false, version: 0, max_alert_level: 0 };
         for i in 0..20u8 {
             let mut k = [0u8; 32];
             k[0] = i;
@@ -637,6 +1446,16 @@ mod tests {
         assert!(conf <= 1.0, "Confidence must be capped at 1.0, got {}", conf);
     }
 
+    #[test]
+    fn test_confidence_established_reporter_outweighs_fresh_one() {
+        // C11: the whole point — a single previously-confirmed key should
+        // score higher confidence than a single brand-new one, for the same
+        // unique-key count.
+        let fresh = GeoCluster { keys: HashSet::new(), untrusted_keys: HashSet::new(), total: 1, first_ms: 0, weighted_total: 1.0, credited: false, notified: false, version: 0, max_alert_level: 0 };
+        let established = GeoCluster { keys: HashSet::new(), untrusted_keys: HashSet::new(), total: 1, first_ms: 0, weighted_total: reputation::MAX_KEY_WEIGHT, credited: false, notified: false, version: 0, max_alert_level: 0 };
+        assert!(established.confidence() > fresh.confidence());
+    }
+
     #[test]
     fn test_time_bucket() {
         let t1 = time_bucket(1000);
@@ -646,75 +1465,6 @@ mod tests {
         assert_ne!(t2, t3); // Different minutes
     }
 
-    #[test]
-    fn test_verify_sig_valid_roundtrip() {
-        use ed25519_dalek::{SigningKey, Signer};
-        use rand::rngs::OsRng;
-
-        // Generate a real keypair
-        let sk = SigningKey::generate(&mut OsRng);
-        let vk = sk.verifying_key();
-
-        // Build a packet WITHOUT signature
-        let mut p = proto::SinyalistPacket::default();
-        p.user_id = 42;
-        p.timestamp_ms = 1700000000000;
-        p.latitude_e7 = 410000000;
-        p.longitude_e7 = 290000000;
-        p.packet_id = vec![1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16];
-        p.ed25519_public_key = vk.to_bytes().to_vec();
-
-        // Serialize without signature to get signing bytes
-        let mut signing_bytes = Vec::with_capacity(p.encoded_len());
-        p.encode(&mut signing_bytes).unwrap();
-
-        // Sign
-        let sig = sk.sign(&signing_bytes);
-        p.ed25519_signature = sig.to_bytes().to_vec();
-
-        // Verify
-        assert!(verify_sig(&p), "Valid signature should pass verification");
-    }
-
-    #[test]
-    fn test_verify_sig_detects_tampering() {
-        use ed25519_dalek::{SigningKey, Signer};
-        use rand::rngs::OsRng;
-
-        let sk = SigningKey::generate(&mut OsRng);
-        let vk = sk.verifying_key();
-
-        let mut p = proto::SinyalistPacket::default();
-        p.user_id = 42;
-        p.timestamp_ms = 1700000000000;
-        p.ed25519_public_key = vk.to_bytes().to_vec();
-
-        let mut signing_bytes = Vec::with_capacity(p.encoded_len());
-        p.encode(&mut signing_bytes).unwrap();
-
-        let sig = sk.sign(&signing_bytes);
-        p.ed25519_signature = sig.to_bytes().to_vec();
-
-        // Tamper with a field AFTER signing
-        p.user_id = 99;
-
-        assert!(!verify_sig(&p), "Tampered packet should fail verification");
-    }
-
-    #[test]
-    fn test_verify_sig_rejects_wrong_lengths() {
-        let mut p = proto::SinyalistPacket::default();
-        p.ed25519_public_key = vec![0u8; 16]; // Wrong length
-        p.ed25519_signature = vec![0u8; 64];
-        assert!(!verify_sig(&p));
-    }
-
-    #[test]
-    fn test_verify_sig_rejects_empty() {
-        let p = proto::SinyalistPacket::default();
-        assert!(!verify_sig(&p));
-    }
-
     #[test]
     fn test_timestamp_validation_window() {
         use std::time::{SystemTime, UNIX_EPOCH};
@@ -741,15 +1491,38 @@ mod tests {
     }
 
     #[test]
-    fn test_consensus_threshold() {
+    fn test_consensus_threshold_with_fresh_keys_matches_old_flat_count() {
+        // Three fresh keys (weight 1.0 each) reach the threshold, same as
+        // the old flat `CONSENSUS_MIN_DEVICES == 3` behavior.
         let mut c = GeoCluster::default();
-        // Below threshold
+        c.weighted_total += reputation::BASE_WEIGHT;
         c.keys.insert([1u8; 32]);
+        c.weighted_total += reputation::BASE_WEIGHT;
         c.keys.insert([2u8; 32]);
-        assert!(c.keys.len() < CONSENSUS_MIN_DEVICES, "2 devices should be below consensus threshold");
-        // At threshold
+        assert!(c.weighted_total < CONSENSUS_WEIGHT_THRESHOLD, "2 fresh devices should be below consensus threshold");
+        c.weighted_total += reputation::BASE_WEIGHT;
         c.keys.insert([3u8; 32]);
-        assert!(c.keys.len() >= CONSENSUS_MIN_DEVICES, "3 devices should reach consensus threshold");
+        assert!(c.weighted_total >= CONSENSUS_WEIGHT_THRESHOLD, "3 fresh devices should reach consensus threshold");
+    }
+
+    #[test]
+    fn test_consensus_reachable_by_fewer_established_keys() {
+        // C11: a handful of established devices should reach consensus
+        // without needing three of them, the way three fresh keys do.
+        let mut c = GeoCluster::default();
+        c.weighted_total += reputation::MAX_KEY_WEIGHT;
+        c.keys.insert([1u8; 32]);
+        assert!(c.weighted_total >= CONSENSUS_WEIGHT_THRESHOLD, "a single maximally-established key should alone reach consensus");
+    }
+
+    #[test]
+    fn test_sybil_flood_of_fresh_keys_capped_below_established_pair() {
+        // C11: two established keys should be able to match or beat what it
+        // takes many-but-still-capped fresh Sybil keys to reach, since each
+        // fresh key only ever contributes `BASE_WEIGHT`.
+        let two_established = 2.0 * reputation::MAX_KEY_WEIGHT;
+        let three_fresh = 3.0 * reputation::BASE_WEIGHT;
+        assert!(two_established >= three_fresh);
     }
 
     #[test]
@@ -757,4 +1530,186 @@ mod tests {
         assert_eq!(hex_encode(&[0xDE, 0xAD, 0xBE, 0xEF]), "deadbeef");
         assert_eq!(hex_encode(&[]), "");
     }
+
+    /// The receiving end of every channel `AppState` sends into, kept alive
+    /// by `test_app_state`'s caller so the channels stay open for as long as
+    /// its `AppState` is in use — `process_packet` only ever `try_send`s
+    /// into them, never reads, so nothing needs to drain these.
+    type TestChannelReceivers = (mpsc::Receiver<(String, proto::SinyalistPacket)>, mpsc::Receiver<proto::SinyalistPacket>, mpsc::Receiver<hooks::HookEvent>);
+
+    /// A minimal `AppState` wired up the same way `main()` builds it, but
+    /// with no workers spawned on the other end of its channels.
+    fn test_app_state() -> (AppState, TestChannelReceivers) {
+        let (ptx, prx) = mpsc::channel(8);
+        let (atx, arx) = mpsc::channel(8);
+        let (htx, hrx) = mpsc::channel(8);
+        let s = AppState {
+            dedup: Arc::new(TtlMap::with_capacity(16)),
+            persist_tx: ptx,
+            afad_tx: atx,
+            m: Arc::new(Metrics::new()),
+            rl_key: Arc::new(TtlMap::with_capacity(16)),
+            rl_geo: Arc::new(TtlMap::with_capacity(16)),
+            clusters: Arc::new(DashMap::new()),
+            known_keys: Arc::new(DashMap::new()),
+            merkle: Arc::new(RwLock::new(MerkleLog::new())),
+            ingest_index: Arc::new(DashMap::new()),
+            backend: Arc::from(storage::build_backend("/tmp/sinyalist-test-process-packet")),
+            keyring: Arc::new(KeyRing::load_or_generate()),
+            subscribe_hub: Arc::new(subscribe::Hub::new()),
+            trust_policy: Arc::new(trust::TrustPolicy::Open),
+            gossip_secret: None,
+            hook_tx: htx,
+        };
+        (s, (prx, arx, hrx))
+    }
+
+    /// Builds a 4-packet Merkle batch (mirroring lib.rs's own
+    /// `test_verify_sig_accepts_valid_merkle_batch_packet`) and returns the
+    /// packet at `idx`, fully wired up with `merkle_root`/`merkle_signature`/
+    /// `merkle_proof`/`leaf_index` and an empty `ed25519_signature` — the
+    /// shape `tools/loadtest`'s `--batch` mode posts to `/v1/ingest`.
+    fn merkle_batch_packet(idx: usize) -> proto::SinyalistPacket {
+        use ed25519_dalek::SigningKey;
+        use sinyalist_ingest::merkle_batch;
+
+        let sk = SigningKey::generate(&mut rand::rngs::OsRng);
+        let mut packets: Vec<proto::SinyalistPacket> = (0..4)
+            .map(|i| proto::SinyalistPacket {
+                user_id: i as u64 + 1,
+                timestamp_ms: 1_700_000_000_000,
+                packet_id: vec![i as u8; 4],
+                ed25519_public_key: sk.verifying_key().to_bytes().to_vec(),
+                ..Default::default()
+            })
+            .collect();
+        // merkle_root/merkle_signature/merkle_proof/leaf_index are all still
+        // default/empty at this point, so the packet's own encoding already
+        // matches `verify_sig`'s signing-bytes convention (lib.rs).
+        let leaves: Vec<merkle_batch::Hash> = packets
+            .iter()
+            .map(|p| {
+                let mut buf = Vec::with_capacity(p.encoded_len());
+                p.encode(&mut buf).expect("packet always encodes");
+                merkle_batch::leaf_hash(&buf)
+            })
+            .collect();
+        let (root, proofs) = merkle_batch::build_tree(&leaves);
+        let p = &mut packets[idx];
+        p.merkle_root = root.to_vec();
+        p.merkle_signature = sk.sign(&root).to_bytes().to_vec();
+        p.merkle_proof = proofs[idx].iter().map(|h| h.to_vec()).collect();
+        p.leaf_index = idx as u32;
+        packets.swap_remove(idx)
+    }
+
+    #[tokio::test]
+    async fn test_process_packet_accepts_merkle_batched_packet() {
+        // The bug this guards against: the signature-presence gate used to
+        // reject every Merkle-batched packet (empty `ed25519_signature` by
+        // design) before `verify_sig` ever got a chance to check
+        // `merkle_signature` — see the gate above.
+        let (s, _rx) = test_app_state();
+        let p = merkle_batch_packet(1);
+        let (status, _headers, ack) = process_packet(s, p, None).await;
+        assert_eq!(status, StatusCode::OK, "a validly merkle-signed packet should be accepted");
+        let parsed = proto::PacketAck::decode(ack.as_ref()).expect("ack should decode");
+        assert!(parsed.received, "ack should report the packet as received");
+    }
+
+    #[tokio::test]
+    async fn test_process_packet_rejects_merkle_batched_packet_with_tampered_proof() {
+        let (s, _rx) = test_app_state();
+        let mut p = merkle_batch_packet(2);
+        p.merkle_proof[0][0] ^= 0xFF;
+        let (status, _headers, _ack) = process_packet(s, p, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "a tampered merkle proof must not verify");
+    }
+
+    #[tokio::test]
+    async fn test_ingest_batch_accepts_merkle_batched_packet() {
+        // The bug this guards against: `sigverify::cheaply_rejects` used to
+        // reject every Merkle-batched packet outright (empty
+        // `ed25519_signature` by design) before `verify_batch`/
+        // `verify_batch_parallel` ever checked `merkle_signature` — so
+        // `POST /v1/ingest/batch` rejected every merkle-batched packet even
+        // though `process_packet`'s own signature-presence gate (tested
+        // separately above) had long since been fixed to accept them. This
+        // drives the exact pipeline `ingest_batch` runs: `verify_batch_parallel`
+        // produces the `SigCheck`, which `process_packet` then consumes —
+        // unlike the `precomputed_sig_check: None` used elsewhere in this
+        // file, which bypasses `sigverify.rs` entirely.
+        let (s, _rx) = test_app_state();
+        let p = merkle_batch_packet(1);
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let checks = sigverify::verify_batch_parallel(&[p.clone()], now);
+        assert_eq!(checks, vec![sigverify::SigCheck::Valid], "a validly merkle-signed packet must pass sigverify's batch path");
+        let (status, _headers, ack) = process_packet(s, p, Some(checks[0])).await;
+        assert_eq!(status, StatusCode::OK, "a validly merkle-signed batched packet should be accepted");
+        let parsed = proto::PacketAck::decode(ack.as_ref()).expect("ack should decode");
+        assert!(parsed.received, "ack should report the packet as received");
+    }
+
+    #[tokio::test]
+    async fn test_process_packet_reports_within_batch_duplicate_as_dedup_not_verify_fail() {
+        // A `packet_id` repeated within one /v1/ingest/batch body is a benign
+        // duplicate, not a forged signature — `sigverify::SigCheck::DuplicatePacketId`
+        // must surface as the same 200 "already_accepted" a cross-request dedup
+        // hit gets, and must not increment `verify_fail`.
+        let (s, _rx) = test_app_state();
+        let p = proto::SinyalistPacket {
+            user_id: 1,
+            timestamp_ms: 1_700_000_000_000,
+            packet_id: vec![9, 9, 9],
+            ed25519_public_key: vec![1; 32],
+            ed25519_signature: vec![2; 64],
+            ..Default::default()
+        };
+        let verify_fail_before = s.m.verify_fail.load(Ordering::Relaxed);
+        let (status, _headers, ack) = process_packet(s.clone(), p, Some(sigverify::SigCheck::DuplicatePacketId)).await;
+        assert_eq!(status, StatusCode::OK, "an in-batch duplicate packet_id should be treated as dedup, not a signature failure");
+        assert_eq!(s.m.verify_fail.load(Ordering::Relaxed), verify_fail_before, "a dedup drop must not count against verify_fail");
+        let parsed = proto::PacketAck::decode(ack.as_ref()).expect("ack should decode");
+        assert_eq!(parsed.status, "already_accepted");
+    }
+
+    #[tokio::test]
+    async fn test_process_packet_fires_hook_exactly_once_per_cluster() {
+        // C16: the packet that pushes a cluster's `weighted_total` past
+        // `CONSENSUS_WEIGHT_THRESHOLD` must emit exactly one `HookEvent`:
+        // drives `process_packet` across the threshold once, then sends one
+        // more packet into the same (geo_key, time_bucket) to assert the
+        // already-confirmed cluster doesn't re-fire.
+        let (s, (_prx, _arx, mut hrx)) = test_app_state();
+        let packet = |n: u8| proto::SinyalistPacket {
+            user_id: n as u64 + 1,
+            timestamp_ms: 1_700_000_000_000,
+            packet_id: vec![n],
+            latitude_e7: 410_000_000,
+            longitude_e7: 290_000_000,
+            ed25519_public_key: vec![n; 32],
+            ed25519_signature: vec![1; 64],
+            ..Default::default()
+        };
+
+        // BASE_WEIGHT == 1.0 per fresh key (reputation.rs), so 3 distinct
+        // trusted keys reach the 3.0 threshold exactly on the third packet.
+        for n in 0..2u8 {
+            let (status, _headers, _ack) = process_packet(s.clone(), packet(n), Some(sigverify::SigCheck::Valid)).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        assert!(hrx.try_recv().is_err(), "no hook should fire before the cluster reaches consensus");
+
+        let (status, _headers, _ack) = process_packet(s.clone(), packet(2), Some(sigverify::SigCheck::Valid)).await;
+        assert_eq!(status, StatusCode::OK);
+        let ev = hrx.try_recv().expect("the packet that crosses the threshold must fire exactly one hook event");
+        assert_eq!(ev.reporter_count, 3);
+        assert!(hrx.try_recv().is_err(), "only one hook event should fire on the crossing packet");
+
+        // A further packet into the same already-confirmed cluster must not
+        // re-fire — `GeoCluster.notified` gates exactly like `credited` does.
+        let (status, _headers, _ack) = process_packet(s.clone(), packet(3), Some(sigverify::SigCheck::Valid)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(hrx.try_recv().is_err(), "a packet into an already-notified cluster must not re-fire the hook");
+    }
 }
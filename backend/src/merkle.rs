@@ -0,0 +1,265 @@
+// =============================================================================
+// SINYALIST — Merkle Mountain Range accumulator (persist path)
+// =============================================================================
+// Gives AFAD / external auditors a tamper-evident commitment over every
+// accepted packet: each persisted record becomes a leaf
+// `h = SHA-256(0x00 || record)`, and internal nodes are
+// `SHA-256(0x01 || left || right)` — the `0x00`/`0x01` domain-separation
+// prefixes (RFC 6962-style) keep a forged record whose bytes happen to
+// equal two sibling hashes from being confused with the internal node
+// above them. Peaks are "bagged" periodically into a single signed root
+// (see `checkpoint_worker` in main.rs). Anyone holding an `InclusionProof` can
+// verify a specific packet was accepted without trusting the server not to
+// have silently dropped or altered it afterwards — omission shows up as a
+// missing leaf, alteration as a root mismatch.
+//
+// Peaks: an MMR keeps one hash per "mountain" — a complete binary subtree of
+// size 2^h. Appending a leaf turns it into a new height-0 peak, then merges
+// it leftward with any existing peaks of the same height (mirroring carries
+// in the binary representation of the leaf count). Appends are therefore
+// O(log n), and an append never rewrites a hash that's already been
+// committed to a published root.
+// =============================================================================
+
+use sha2::{Digest, Sha256};
+
+pub type Hash = [u8; 32];
+
+fn hash_leaf(record: &[u8]) -> Hash {
+    let mut h = Sha256::new();
+    h.update([0x00]);
+    h.update(record);
+    h.finalize().into()
+}
+
+fn hash_pair(left: &Hash, right: &Hash) -> Hash {
+    let mut h = Sha256::new();
+    h.update([0x01]);
+    h.update(left);
+    h.update(right);
+    h.finalize().into()
+}
+
+/// One step of an [`InclusionProof`]: the sibling hash to combine with the
+/// hash accumulated so far, and which side it sits on.
+#[derive(Clone, Debug)]
+pub struct ProofStep {
+    pub hash: Hash,
+    /// `true` if `hash` is the left operand of the next `hash_pair` (i.e.
+    /// the hash accumulated so far is the right operand).
+    pub left: bool,
+}
+
+/// Proof that the leaf at `leaf_index` was included under `root` when the
+/// log held `leaf_count` leaves — an intra-peak sibling path up to the
+/// leaf's mountain, followed by the peak-bagging path to the root.
+#[derive(Clone, Debug)]
+pub struct InclusionProof {
+    pub leaf_index: u64,
+    pub leaf_count: u64,
+    pub root: Hash,
+    pub path: Vec<ProofStep>,
+}
+
+impl InclusionProof {
+    /// Recomputes the root from `leaf_hash` and the proof path, for callers
+    /// who want to check a proof without trusting this module's `root()`.
+    pub fn verify(&self, leaf_hash: &Hash) -> bool {
+        let mut acc = *leaf_hash;
+        for step in &self.path {
+            acc = if step.left { hash_pair(&step.hash, &acc) } else { hash_pair(&acc, &step.hash) };
+        }
+        acc == self.root
+    }
+}
+
+/// Append-only Merkle Mountain Range over persisted record hashes.
+#[derive(Default)]
+pub struct MerkleLog {
+    leaves: Vec<Hash>,
+    /// Current peaks, ordered earliest (largest) to most recent (smallest)
+    /// — the same order `root`/`prove` bag them in.
+    peaks: Vec<(u32, Hash)>,
+}
+
+impl MerkleLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn leaf_count(&self) -> u64 {
+        self.leaves.len() as u64
+    }
+
+    /// Appends one record's canonical bytes as a new leaf, returning its
+    /// 0-based leaf index. O(log n): at most one merge per existing peak
+    /// height, and no existing leaf or peak hash is ever rewritten.
+    pub fn append(&mut self, record: &[u8]) -> u64 {
+        let leaf = hash_leaf(record);
+        let index = self.leaves.len() as u64;
+        self.leaves.push(leaf);
+
+        let mut height = 0u32;
+        let mut hash = leaf;
+        while let Some(&(h, top)) = self.peaks.last() {
+            if h != height {
+                break;
+            }
+            hash = hash_pair(&top, &hash);
+            height += 1;
+            self.peaks.pop();
+        }
+        self.peaks.push((height, hash));
+        index
+    }
+
+    /// Bags the current peaks into a single root:
+    /// `H(peak_0, H(peak_1, H(peak_2, ... peak_last)))`.
+    pub fn root(&self) -> Hash {
+        let mut acc: Option<Hash> = None;
+        for &(_, h) in self.peaks.iter().rev() {
+            acc = Some(match acc {
+                None => h,
+                Some(a) => hash_pair(&h, &a),
+            });
+        }
+        acc.unwrap_or([0u8; 32])
+    }
+
+    /// Builds an [`InclusionProof`] for `leaf_index` against the *current*
+    /// root. Returns `None` if the index is out of range.
+    pub fn prove(&self, leaf_index: u64) -> Option<InclusionProof> {
+        if leaf_index >= self.leaf_count() {
+            return None;
+        }
+
+        // Find the peak (mountain) covering `leaf_index` and its local offset.
+        let mut start = 0u64;
+        let mut found = None;
+        for (i, &(h, _)) in self.peaks.iter().enumerate() {
+            let size = 1u64 << h;
+            if leaf_index < start + size {
+                found = Some((i, start, size));
+                break;
+            }
+            start += size;
+        }
+        let (peak_idx, start, size) = found?;
+
+        // Intra-peak path: a standard perfect-binary-tree sibling path from
+        // the leaf up to the peak hash.
+        let mut layer: Vec<Hash> = self.leaves[start as usize..(start + size) as usize].to_vec();
+        let mut idx = (leaf_index - start) as usize;
+        let mut path = Vec::new();
+        while layer.len() > 1 {
+            let sibling = idx ^ 1;
+            path.push(ProofStep { hash: layer[sibling], left: idx % 2 == 1 });
+            layer = layer.chunks(2).map(|c| hash_pair(&c[0], &c[1])).collect();
+            idx /= 2;
+        }
+
+        // Peak-bagging path: fold in the peaks after ours (if any) first,
+        // then walk leftward through the earlier peaks — the same order
+        // `root` uses to bag the full peak list.
+        let mut acc: Option<Hash> = None;
+        for &(_, h) in self.peaks[peak_idx + 1..].iter().rev() {
+            acc = Some(match acc {
+                None => h,
+                Some(a) => hash_pair(&h, &a),
+            });
+        }
+        if let Some(suffix) = acc {
+            path.push(ProofStep { hash: suffix, left: false });
+        }
+        for &(_, h) in self.peaks[..peak_idx].iter().rev() {
+            path.push(ProofStep { hash: h, left: true });
+        }
+
+        Some(InclusionProof { leaf_index, leaf_count: self.leaf_count(), root: self.root(), path })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rec(n: u8) -> Vec<u8> {
+        vec![n; 8]
+    }
+
+    #[test]
+    fn test_append_is_deterministic() {
+        let mut a = MerkleLog::new();
+        let mut b = MerkleLog::new();
+        for n in 0..9u8 {
+            a.append(&rec(n));
+            b.append(&rec(n));
+        }
+        assert_eq!(a.root(), b.root());
+    }
+
+    #[test]
+    fn test_root_changes_on_append() {
+        let mut m = MerkleLog::new();
+        m.append(&rec(1));
+        let r1 = m.root();
+        m.append(&rec(2));
+        assert_ne!(r1, m.root());
+    }
+
+    #[test]
+    fn test_single_leaf_proof() {
+        let mut m = MerkleLog::new();
+        m.append(&rec(1));
+        let proof = m.prove(0).unwrap();
+        assert!(proof.path.is_empty());
+        assert_eq!(proof.root, m.root());
+        assert!(proof.verify(&hash_leaf(&rec(1))));
+    }
+
+    #[test]
+    fn test_proof_verifies_for_every_leaf_across_sizes() {
+        // Leaf counts 1..19 have 1 to 4 set bits (peaks), covering single- and
+        // multi-peak bagging paths.
+        for n in 1..20u8 {
+            let mut m = MerkleLog::new();
+            for i in 0..n {
+                m.append(&rec(i));
+            }
+            for i in 0..n {
+                let proof = m.prove(i as u64).expect("leaf in range");
+                assert_eq!(proof.leaf_count, n as u64);
+                assert!(proof.verify(&hash_leaf(&rec(i))), "leaf {i} of {n} failed to verify");
+            }
+        }
+    }
+
+    #[test]
+    fn test_proof_rejects_wrong_leaf() {
+        let mut m = MerkleLog::new();
+        for i in 0..5u8 {
+            m.append(&rec(i));
+        }
+        let proof = m.prove(2).unwrap();
+        assert!(!proof.verify(&hash_leaf(&rec(99))));
+    }
+
+    #[test]
+    fn test_prove_out_of_range_returns_none() {
+        let mut m = MerkleLog::new();
+        m.append(&rec(1));
+        assert!(m.prove(5).is_none());
+    }
+
+    #[test]
+    fn test_tamper_detected_via_root_mismatch() {
+        let mut m = MerkleLog::new();
+        for i in 0..4u8 {
+            m.append(&rec(i));
+        }
+        let proof = m.prove(1).unwrap();
+        // A proof computed against the real log must not verify against a
+        // hash for a record that was never appended (simulated tampering).
+        assert!(!proof.verify(&hash_leaf(&rec(200))));
+    }
+}
@@ -0,0 +1,146 @@
+// =============================================================================
+// SINYALIST — stake-weighted reporter reputation
+// =============================================================================
+// `GeoCluster::confidence()` and the consensus gate used to treat every
+// unique pubkey identically, so a cluster of brand-new throwaway keys scored
+// the same as one built from long-lived, previously-trusted devices.
+// `known_keys` (in `main.rs`) already records each key's first-seen time;
+// this module turns that — plus how many clusters the key has previously
+// helped push to consensus — into a `weight()` that `GeoCluster` sums instead
+// of counting unique keys.
+//
+// Borrows Solana's stake-weighting: every key's weight is capped
+// (`MAX_KEY_WEIGHT`), so a Sybil flood of fresh keys (each starting at
+// `BASE_WEIGHT`) can't out-weigh a handful of established devices the way it
+// could out-count them under the old flat scheme.
+//
+// That's a narrower guarantee than "a Sybil flood can't reach consensus at
+// all" — `CONSENSUS_WEIGHT_THRESHOLD` in `main.rs` is sized to match the old
+// flat-count threshold for all-fresh keys, so enough freshly minted keys
+// still sum to it, same as before this module existed. Weighting rewards
+// established reporters; it doesn't gate out floods of new ones by itself.
+// In the default `trust::TrustPolicy::Open`, stopping that requires running
+// in `Allowlist` or `SharedSecret` mode instead (trust.rs, C15).
+// =============================================================================
+
+use dashmap::DashMap;
+
+/// What every newly-seen key starts at — age and history only ever add on
+/// top, so a single first-time reporter still counts for something.
+pub const BASE_WEIGHT: f32 = 1.0;
+/// No single key can contribute more than this, however old or however many
+/// clusters it's confirmed. The cap sits above `CONSENSUS_WEIGHT_THRESHOLD`
+/// (3.0), so one fully established key does reach consensus on its own.
+pub const MAX_KEY_WEIGHT: f32 = 5.0;
+const AGE_WEIGHT: f32 = 2.0;
+const HISTORY_WEIGHT: f32 = 2.0;
+// Age saturates after two days — long enough that a burner key can't "age
+// into" trust between one seismic event and the next.
+const AGE_SATURATION_MS: f64 = 2.0 * 24.0 * 60.0 * 60.0 * 1000.0;
+
+/// Per-key reputation state, keyed by Ed25519 public key in `known_keys`.
+#[derive(Clone, Copy, Debug)]
+pub struct KeyReputation {
+    pub first_seen_ms: u64,
+    pub confirmed_clusters: u32,
+}
+
+impl KeyReputation {
+    pub fn new(now_ms: u64) -> Self {
+        Self { first_seen_ms: now_ms, confirmed_clusters: 0 }
+    }
+
+    /// `BASE_WEIGHT` plus an age term and a history term, each saturating,
+    /// summed and capped at `MAX_KEY_WEIGHT`. The history term reuses
+    /// `GeoCluster::confidence()`'s log-scale shape (diminishing returns per
+    /// additional confirmed cluster, same as diminishing returns per
+    /// additional reporter there).
+    pub fn weight(&self, now_ms: u64) -> f32 {
+        let age_ms = now_ms.saturating_sub(self.first_seen_ms) as f64;
+        let age_term = AGE_WEIGHT * (age_ms / AGE_SATURATION_MS).min(1.0) as f32;
+        let history_term = HISTORY_WEIGHT * ((self.confirmed_clusters as f32 + 1.0).ln() / 3.0).min(1.0);
+        (BASE_WEIGHT + age_term + history_term).min(MAX_KEY_WEIGHT)
+    }
+}
+
+/// Looks up `key`'s current weight in `known_keys`, defaulting to
+/// `BASE_WEIGHT` for a key genuinely no node has reported on yet. A key that
+/// only shows up via a remote gossip peer usually won't hit this default at
+/// all — `gossip::merge_delta` reconciles the peer's own `KeyReputation` view
+/// into `known_keys` before this is ever called, so a key established
+/// elsewhere on the network is credited its real age/history here too.
+pub fn weight_of(known_keys: &DashMap<Vec<u8>, KeyReputation>, key: &[u8], now_ms: u64) -> f32 {
+    known_keys.get(key).map(|r| r.weight(now_ms)).unwrap_or(BASE_WEIGHT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fresh_key_weighs_base() {
+        let r = KeyReputation::new(1_000);
+        assert_eq!(r.weight(1_000), BASE_WEIGHT);
+    }
+
+    #[test]
+    fn test_weight_grows_with_age_then_saturates() {
+        let r = KeyReputation::new(0);
+        let mid = r.weight(AGE_SATURATION_MS as u64 / 2);
+        let full = r.weight(AGE_SATURATION_MS as u64);
+        let past = r.weight(AGE_SATURATION_MS as u64 * 10);
+        assert!(mid > BASE_WEIGHT && mid < full, "weight should grow with age before saturating");
+        assert_eq!(full, past, "age term must saturate, not keep growing past AGE_SATURATION_MS");
+    }
+
+    #[test]
+    fn test_weight_grows_with_confirmed_history_then_caps() {
+        let fresh = KeyReputation::new(0).weight(0);
+        let seasoned = KeyReputation { first_seen_ms: 0, confirmed_clusters: 50 }.weight(0);
+        assert!(seasoned > fresh, "a key with confirmed history should outweigh a fresh one");
+        assert!(seasoned <= MAX_KEY_WEIGHT);
+    }
+
+    #[test]
+    fn test_weight_capped_even_with_max_age_and_history() {
+        let r = KeyReputation { first_seen_ms: 0, confirmed_clusters: 1_000_000 };
+        assert_eq!(r.weight(u64::MAX), MAX_KEY_WEIGHT);
+    }
+
+    #[test]
+    fn test_capped_veteran_outweighs_small_fresh_flood() {
+        // A single long-lived, previously-confirmed key should weigh close
+        // to a handful of brand-new throwaway keys — out-counting it takes
+        // real numbers, not just fresh pubkeys.
+        let veteran = KeyReputation { first_seen_ms: 0, confirmed_clusters: 100 }.weight(u64::MAX);
+        let fresh_flood: f32 = (0..4).map(|_| KeyReputation::new(0).weight(0)).sum();
+        assert!(veteran >= fresh_flood, "one capped veteran key should outweigh 4 fresh Sybil keys");
+    }
+
+    #[test]
+    fn test_fresh_key_flood_still_crosses_consensus_weight_threshold() {
+        // NOT a guarantee of Sybil resistance: documents that in the
+        // default `Open` trust mode, enough freshly minted keys (each at
+        // `BASE_WEIGHT`) sum to `CONSENSUS_WEIGHT_THRESHOLD` in `main.rs`
+        // exactly as the old flat device count did. Weighting alone doesn't
+        // stop this flood — only `Allowlist`/`SharedSecret` trust modes do
+        // (trust.rs, C15).
+        const CONSENSUS_WEIGHT_THRESHOLD: f32 = 3.0;
+        let three_fresh: f32 = (0..3).map(|_| KeyReputation::new(0).weight(0)).sum();
+        assert!(three_fresh >= CONSENSUS_WEIGHT_THRESHOLD,
+            "3 fresh Sybil keys reach the same threshold 3 fresh devices always did — weighting doesn't gate this out");
+    }
+
+    #[test]
+    fn test_weight_of_defaults_unknown_key_to_base() {
+        let known: DashMap<Vec<u8>, KeyReputation> = DashMap::new();
+        assert_eq!(weight_of(&known, b"nope", 0), BASE_WEIGHT);
+    }
+
+    #[test]
+    fn test_weight_of_reads_known_key() {
+        let known: DashMap<Vec<u8>, KeyReputation> = DashMap::new();
+        known.insert(b"k".to_vec(), KeyReputation { first_seen_ms: 0, confirmed_clusters: 50 });
+        assert!(weight_of(&known, b"k", 0) > BASE_WEIGHT);
+    }
+}
@@ -0,0 +1,359 @@
+// =============================================================================
+// SINYALIST — batched Ed25519 verification for packet ingestion (C13)
+// =============================================================================
+// `verify_sig` (lib.rs) checks one packet at a time — fine for steady
+// traffic, but a burst of reports into a single geo cell (the exact moment
+// consensus-worthy reports spike) turns single-signature verification into
+// the ingest bottleneck. `verify_batch` is Solana's sigverify stage adapted
+// to this wire format: reject cheaply-detectable junk (bad key/signature
+// lengths, a timestamp outside the same acceptance window `process_packet`
+// enforces, a `packet_id` already seen earlier in the batch) without ever
+// touching a curve operation, then hand the survivors' reconstructed signing
+// bytes to `ed25519_dalek::verify_batch` (needs the crate's `batch` feature)
+// as one constant-time batch.
+//
+// `verify_batch` only reports pass/fail for the whole batch, so on its own a
+// single forged signature would sink every packet batched alongside it.
+// `verify_recursive` falls back to divide-and-conquer on failure — split in
+// half, recurse, merge — so one bad signature among N costs O(log N) extra
+// batch-verify calls instead of invalidating the burst.
+//
+// `verify_batch_parallel` shards large batches across a rayon pool: each
+// shard runs the full cheap-reject + divide-and-conquer pipeline
+// independently, so shards scale across cores the same way `gossip_worker`'s
+// peer fanout scales across the network. Sharding does mean a duplicate
+// `packet_id` landing in two different shards isn't caught by this pass —
+// that's still caught downstream by `process_packet`'s per-packet dedup.
+//
+// A Merkle-batch-signed packet (C chunk3-1) leaves `ed25519_signature` empty
+// and carries `merkle_root`/`merkle_signature`/`merkle_proof`/`leaf_index`
+// instead — `cheaply_rejects` checks its inclusion proof (SHA-256, still no
+// curve op) in place of a signature-length check, and the curve-verify batch
+// checks `merkle_signature` against the recomputed root rather than the
+// packet's own signing bytes. Mirrors `lib.rs::verify_sig`'s merkle branch.
+// =============================================================================
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use sinyalist_ingest::merkle_batch;
+use sinyalist_ingest::proto;
+use std::collections::HashSet;
+
+/// Batches at least this large get sharded across rayon; below it, a single
+/// thread's divide-and-conquer pass is cheaper than the sharding overhead.
+const PARALLEL_SHARD_MIN: usize = 64;
+const SHARD_SIZE: usize = 32;
+
+/// Per-packet outcome of `verify_batch`/`verify_batch_parallel`. Kept
+/// distinct from a plain bool so a within-batch repeat of a `packet_id`
+/// doesn't get reported the same way as an actually-bad signature:
+/// `process_packet` treats `DuplicatePacketId` as a dedup drop (the same
+/// "already accepted" response a repeat across two separate requests gets),
+/// not a crypto failure — it's never counted against `verify_fail`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SigCheck {
+    Valid,
+    Invalid,
+    DuplicatePacketId,
+}
+
+impl SigCheck {
+    pub fn is_valid(self) -> bool {
+        self == SigCheck::Valid
+    }
+}
+
+/// Wrong-length key/signature, a failing Merkle inclusion proof
+/// (merkle-batched packets carry `merkle_signature` instead and leave
+/// `ed25519_signature` empty — see below), or a `created_at_ms` outside the
+/// same past/future window `process_packet` enforces — checks that cost
+/// nothing next to a curve operation, so they run before any packet reaches
+/// `ed25519_dalek::verify_batch`. The inclusion proof is SHA-256 hashing, not
+/// a curve op, so it belongs here rather than in the batch itself.
+fn cheaply_rejects(p: &proto::SinyalistPacket, now_ms: u64) -> bool {
+    if p.ed25519_public_key.len() != 32 {
+        return true;
+    }
+    if !p.merkle_signature.is_empty() {
+        if p.merkle_signature.len() != 64 || merkle_inclusion_root(p).is_none() {
+            return true;
+        }
+    } else if p.ed25519_signature.len() != 64 {
+        return true;
+    }
+    if p.created_at_ms == 0 {
+        return false;
+    }
+    let age_ms = now_ms.saturating_sub(p.created_at_ms);
+    let future_ms = p.created_at_ms.saturating_sub(now_ms);
+    age_ms > crate::TIMESTAMP_PAST_WINDOW_MS || future_ms > crate::TIMESTAMP_FUTURE_WINDOW_MS
+}
+
+/// Signing bytes for `p`: its own encoding with every signature-carrying
+/// field (`ed25519_signature` and the `merkle_*` fields, none of which are
+/// known until after signing) cleared — identical to `verify_sig`'s (lib.rs)
+/// convention, so a signature a client produced for single-packet
+/// verification also verifies here.
+fn signing_bytes(p: &proto::SinyalistPacket) -> Vec<u8> {
+    use prost::Message;
+    let mut sp = p.clone();
+    sp.ed25519_signature.clear();
+    sp.merkle_root.clear();
+    sp.merkle_signature.clear();
+    sp.merkle_proof.clear();
+    sp.leaf_index = 0;
+    let mut buf = Vec::with_capacity(sp.encoded_len());
+    sp.encode(&mut buf).ok();
+    buf
+}
+
+/// For a merkle-batched packet, recomputes its leaf hash from `signing_bytes`
+/// and checks the inclusion proof against `merkle_root`, mirroring
+/// `lib.rs::verify_sig`'s merkle branch. Returns the root once the proof
+/// checks out, so the caller can hand it to `verify_batch`/`verify_recursive`
+/// as the message the batch's single signature actually covers.
+fn merkle_inclusion_root(p: &proto::SinyalistPacket) -> Option<[u8; 32]> {
+    let root = <[u8; 32]>::try_from(p.merkle_root.as_slice()).ok()?;
+    let leaf = merkle_batch::leaf_hash(&signing_bytes(p));
+    let mut proof = Vec::with_capacity(p.merkle_proof.len());
+    for sibling in &p.merkle_proof {
+        proof.push(<[u8; 32]>::try_from(sibling.as_slice()).ok()?);
+    }
+    merkle_batch::verify_proof(leaf, p.leaf_index, &proof, root).then_some(root)
+}
+
+/// Verifies one batch as a single `ed25519_dalek::verify_batch` call; on
+/// failure, splits in half and recurses, so a single bad signature only
+/// costs its own half a second pass rather than sinking every packet in the
+/// batch. Base case: a batch of one that fails is simply `false`.
+fn verify_recursive(messages: &[&[u8]], sigs: &[Signature], keys: &[VerifyingKey]) -> Vec<bool> {
+    if messages.is_empty() {
+        return Vec::new();
+    }
+    if ed25519_dalek::verify_batch(messages, sigs, keys).is_ok() {
+        return vec![true; messages.len()];
+    }
+    if messages.len() == 1 {
+        return vec![false];
+    }
+    let mid = messages.len() / 2;
+    let mut left = verify_recursive(&messages[..mid], &sigs[..mid], &keys[..mid]);
+    left.extend(verify_recursive(&messages[mid..], &sigs[mid..], &keys[mid..]));
+    left
+}
+
+/// Verifies every packet in `batch`, returning one `SigCheck` per input in
+/// the same order. An index is `Invalid` without ever reaching
+/// `verify_batch` if it's cheaply rejected or has a key/signature that
+/// doesn't even parse; an index that repeats an earlier-in-this-batch
+/// non-empty `packet_id` is `DuplicatePacketId` instead — distinct from
+/// `Invalid` so callers don't mistake a benign duplicate for a forged
+/// signature. An empty `packet_id` (main.rs's own dedup falls back to
+/// user_id+timestamp for these) never counts as a duplicate of another
+/// empty one here — two otherwise-distinct packets that both omit it are
+/// not the same packet.
+pub fn verify_batch(batch: &[proto::SinyalistPacket], now_ms: u64) -> Vec<SigCheck> {
+    let mut result = vec![SigCheck::Invalid; batch.len()];
+    let mut seen_ids: HashSet<&[u8]> = HashSet::new();
+    // (original index, signing bytes, key, signature) for everything that
+    // survives the cheap pre-checks and parses as a well-formed key/signature.
+    let mut candidates: Vec<(usize, Vec<u8>, VerifyingKey, Signature)> = Vec::with_capacity(batch.len());
+
+    for (i, p) in batch.iter().enumerate() {
+        if cheaply_rejects(p, now_ms) {
+            continue;
+        }
+        if !p.packet_id.is_empty() && !seen_ids.insert(p.packet_id.as_slice()) {
+            result[i] = SigCheck::DuplicatePacketId;
+            continue;
+        }
+        let Ok(pk) = <[u8; 32]>::try_from(p.ed25519_public_key.as_slice()) else { continue };
+        let Ok(vk) = VerifyingKey::from_bytes(&pk) else { continue };
+        if !p.merkle_signature.is_empty() {
+            // `cheaply_rejects` already confirmed the inclusion proof holds;
+            // recompute the root it proved into so the batch verifies the
+            // signature against what it actually covers.
+            let Some(root) = merkle_inclusion_root(p) else { continue };
+            let Ok(sg) = <[u8; 64]>::try_from(p.merkle_signature.as_slice()) else { continue };
+            candidates.push((i, root.to_vec(), vk, Signature::from_bytes(&sg)));
+        } else {
+            let Ok(sg) = <[u8; 64]>::try_from(p.ed25519_signature.as_slice()) else { continue };
+            candidates.push((i, signing_bytes(p), vk, Signature::from_bytes(&sg)));
+        }
+    }
+
+    if candidates.is_empty() {
+        return result;
+    }
+
+    let messages: Vec<&[u8]> = candidates.iter().map(|(_, m, _, _)| m.as_slice()).collect();
+    let keys: Vec<VerifyingKey> = candidates.iter().map(|(_, _, k, _)| *k).collect();
+    let sigs: Vec<Signature> = candidates.iter().map(|(_, _, _, s)| *s).collect();
+
+    for ((i, _, _, _), pass) in candidates.iter().zip(verify_recursive(&messages, &sigs, &keys)) {
+        result[*i] = if pass { SigCheck::Valid } else { SigCheck::Invalid };
+    }
+    result
+}
+
+/// Same as `verify_batch`, but shards batches of at least `PARALLEL_SHARD_MIN`
+/// packets across a rayon pool in chunks of `SHARD_SIZE` — each shard's
+/// pre-checks and divide-and-conquer pass are independent of every other
+/// shard's, so this scales across cores the way a single-threaded
+/// `verify_batch` call over the whole burst can't.
+pub fn verify_batch_parallel(batch: &[proto::SinyalistPacket], now_ms: u64) -> Vec<SigCheck> {
+    if batch.len() < PARALLEL_SHARD_MIN {
+        return verify_batch(batch, now_ms);
+    }
+    use rayon::prelude::*;
+    batch.par_chunks(SHARD_SIZE).flat_map(|chunk| verify_batch(chunk, now_ms)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{SigningKey, Signer};
+    use prost::Message;
+    use rand::rngs::OsRng;
+
+    fn signed_packet(sk: &SigningKey, user_id: u64, packet_id: Vec<u8>) -> proto::SinyalistPacket {
+        let mut p = proto::SinyalistPacket {
+            user_id,
+            timestamp_ms: 1_700_000_000_000,
+            created_at_ms: 1_700_000_000_000,
+            latitude_e7: 410_000_000,
+            longitude_e7: 290_000_000,
+            packet_id,
+            ed25519_public_key: sk.verifying_key().to_bytes().to_vec(),
+            ..Default::default()
+        };
+        let mut signing_bytes = Vec::with_capacity(p.encoded_len());
+        p.encode(&mut signing_bytes).unwrap();
+        p.ed25519_signature = sk.sign(&signing_bytes).to_bytes().to_vec();
+        p
+    }
+
+    #[test]
+    fn test_verify_batch_all_valid() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let batch: Vec<_> = (0..10).map(|i| signed_packet(&sk, i, vec![i as u8])).collect();
+        let results = verify_batch(&batch, 1_700_000_000_000);
+        assert!(results.iter().all(|r| r.is_valid()), "every packet has a valid signature");
+    }
+
+    #[test]
+    fn test_verify_batch_one_bad_signature_only_fails_itself() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let mut batch: Vec<_> = (0..8).map(|i| signed_packet(&sk, i, vec![i as u8])).collect();
+        batch[3].latitude_e7 += 1; // invalidates the signing bytes for just this packet
+        let results = verify_batch(&batch, 1_700_000_000_000);
+        for (i, r) in results.iter().enumerate() {
+            assert_eq!(r.is_valid(), i != 3, "only the tampered packet at index 3 should fail");
+        }
+    }
+
+    #[test]
+    fn test_verify_batch_rejects_wrong_length_key_without_panicking() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let mut p = signed_packet(&sk, 1, vec![1]);
+        p.ed25519_public_key.truncate(16);
+        assert_eq!(verify_batch(&[p], 1_700_000_000_000), vec![SigCheck::Invalid]);
+    }
+
+    #[test]
+    fn test_verify_batch_rejects_stale_timestamp() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let p = signed_packet(&sk, 1, vec![1]);
+        let far_future = p.created_at_ms + crate::TIMESTAMP_PAST_WINDOW_MS * 10;
+        assert_eq!(verify_batch(&[p], far_future), vec![SigCheck::Invalid]);
+    }
+
+    #[test]
+    fn test_verify_batch_drops_duplicate_packet_id_within_batch() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let p1 = signed_packet(&sk, 1, vec![9, 9, 9]);
+        let p2 = signed_packet(&sk, 2, vec![9, 9, 9]);
+        let results = verify_batch(&[p1, p2], 1_700_000_000_000);
+        assert_eq!(
+            results,
+            vec![SigCheck::Valid, SigCheck::DuplicatePacketId],
+            "second packet repeats the first's packet_id, and isn't reported as a bad signature"
+        );
+    }
+
+    #[test]
+    fn test_verify_batch_empty_packet_ids_are_not_duplicates_of_each_other() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let p1 = signed_packet(&sk, 1, Vec::new());
+        let p2 = signed_packet(&sk, 2, Vec::new());
+        let results = verify_batch(&[p1, p2], 1_700_000_000_000);
+        assert_eq!(
+            results,
+            vec![SigCheck::Valid, SigCheck::Valid],
+            "two distinct packets that both omit packet_id must not collide as duplicates of each other"
+        );
+    }
+
+    #[test]
+    fn test_verify_batch_parallel_matches_serial_for_large_batch() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let mut batch: Vec<_> = (0..200u64).map(|i| signed_packet(&sk, i, i.to_le_bytes().to_vec())).collect();
+        batch[150].latitude_e7 += 1;
+        let mut results = verify_batch_parallel(&batch, 1_700_000_000_000);
+        for (i, r) in results.drain(..).enumerate() {
+            assert_eq!(r.is_valid(), i != 150, "tampered packet at index 150 should be the only failure");
+        }
+    }
+
+    #[test]
+    fn test_verify_batch_empty_returns_empty() {
+        assert_eq!(verify_batch(&[], 0), Vec::<SigCheck>::new());
+    }
+
+    /// Builds a 4-packet Merkle batch (mirroring `main.rs`'s own
+    /// `merkle_batch_packet` test helper) and returns the packet at `idx`,
+    /// fully wired up with `merkle_root`/`merkle_signature`/`merkle_proof`/
+    /// `leaf_index` and an empty `ed25519_signature` — the shape
+    /// `tools/loadtest`'s `--batch` mode posts, one packet per request, to
+    /// `/v1/ingest`.
+    fn merkle_batch_packet(idx: usize) -> proto::SinyalistPacket {
+        let sk = SigningKey::generate(&mut OsRng);
+        let mut packets: Vec<proto::SinyalistPacket> = (0..4)
+            .map(|i| proto::SinyalistPacket {
+                user_id: i as u64 + 1,
+                timestamp_ms: 1_700_000_000_000,
+                created_at_ms: 1_700_000_000_000,
+                packet_id: vec![i as u8; 4],
+                ed25519_public_key: sk.verifying_key().to_bytes().to_vec(),
+                ..Default::default()
+            })
+            .collect();
+        let leaves: Vec<merkle_batch::Hash> = packets.iter().map(|p| merkle_batch::leaf_hash(&signing_bytes(p))).collect();
+        let (root, proofs) = merkle_batch::build_tree(&leaves);
+        let p = &mut packets[idx];
+        p.merkle_root = root.to_vec();
+        p.merkle_signature = sk.sign(&root).to_bytes().to_vec();
+        p.merkle_proof = proofs[idx].iter().map(|h| h.to_vec()).collect();
+        p.leaf_index = idx as u32;
+        packets.swap_remove(idx)
+    }
+
+    #[test]
+    fn test_verify_batch_accepts_merkle_batched_packet() {
+        // The bug this guards against: `cheaply_rejects` used to reject any
+        // packet whose `ed25519_signature.len() != 64` unconditionally, with
+        // no branch for `merkle_signature` — so every legitimately
+        // merkle-batched packet (empty `ed25519_signature` by design) was
+        // rejected before ever reaching a curve operation.
+        let p = merkle_batch_packet(1);
+        let results = verify_batch(&[p], 1_700_000_000_000);
+        assert_eq!(results, vec![SigCheck::Valid], "a validly merkle-signed packet must pass sigverify's batch path, not just lib.rs's single-packet verify_sig");
+    }
+
+    #[test]
+    fn test_verify_batch_rejects_merkle_batched_packet_with_tampered_proof() {
+        let mut p = merkle_batch_packet(2);
+        p.merkle_proof[0][0] ^= 0xFF;
+        let results = verify_batch(&[p], 1_700_000_000_000);
+        assert_eq!(results, vec![SigCheck::Invalid], "a tampered merkle proof must not verify");
+    }
+}
@@ -0,0 +1,196 @@
+// =============================================================================
+// SINYALIST — at-rest encryption of persisted payloads
+// =============================================================================
+// Persisted records carry PII and medical data (precise lat/lon, pulse,
+// SpO2, blood type, room hint) that a plaintext NDJSON/SQLite/LMDB file on
+// disk has no business holding in the clear. `Payload` is everything that
+// sensitive; `KeyRing::seal` AEAD-encrypts it with a fresh random nonce per
+// record before `flush()` ever hands it to a `PersistBackend`, binding the
+// record's `packet_id_hex` as associated data so a ciphertext can't be
+// replayed under a different packet's identity. `geo_key`/`time_bucket`/
+// `timestamp_ms`/`is_trapped`/`alert_level` stay outside `Payload` — coarse
+// grid cells and booleans a backend needs to index on, not PII on their own.
+//
+// Modeled on Garage's object encryption: a server master key (env or,
+// failing that, an ephemeral one) is used directly as the ChaCha20-Poly1305
+// key new records seal under — no separate data key is derived from it —
+// while every previously-active key stays around to decrypt older
+// records — `SealedPayload::key_id` is the rotation marker that says which.
+// =============================================================================
+
+use chacha20poly1305::aead::{Aead, Payload as AeadPayload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Everything about a persisted packet that's PII or medical data. Only ever
+/// touched through `KeyRing::seal`/`open` — never written to disk in the
+/// clear.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Payload {
+    pub user_id: u64,
+    pub lat_e7: i32,
+    pub lon_e7: i32,
+    pub created_at_ms: u64,
+    pub msg_type: i32,
+    pub pubkey_hex: String,
+    pub room_hint: String,
+    pub blood_type: i32,
+    pub pulse_bpm: u32,
+    pub spo2_percent: u32,
+}
+
+/// An AEAD-sealed `Payload` — nonce plus ciphertext (the Poly1305 tag is
+/// appended to `ciphertext` by the `aead` crate's own convention, the same
+/// way this repo leans on upstream crates for wire framing rather than
+/// hand-rolling it, e.g. prost for `SinyalistPacket`). `key_id` is the
+/// rotation marker: which key in the `KeyRing` sealed this record, so a key
+/// rotation doesn't require re-encrypting every record already on disk.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SealedPayload {
+    pub key_id: u32,
+    pub nonce: [u8; 12],
+    pub ciphertext: Vec<u8>,
+}
+
+/// Every key this node can decrypt with, plus which one new records seal
+/// under. `active` is always present in `keys`.
+pub struct KeyRing {
+    active: u32,
+    keys: HashMap<u32, Key>,
+}
+
+impl KeyRing {
+    /// Loads from `SINYALIST_MASTER_KEYS_HEX` — comma-separated `id:hex64`
+    /// entries (`id` a `u32`, `hex64` a 32-byte key as 64 hex chars), e.g.
+    /// `1:aa..aa,2:bb..bb`. The highest `id` present becomes `active`; every
+    /// id present stays decryptable, which is what lets a rotation add a new
+    /// active key without making older persisted records unreadable.
+    ///
+    /// Falls back to a single ephemeral key (with a warning), same as
+    /// `load_or_generate_log_signing_key` in `main.rs` — persisted records
+    /// still round-trip within one run, they just won't decrypt after a
+    /// restart.
+    pub fn load_or_generate() -> Self {
+        if let Ok(spec) = std::env::var("SINYALIST_MASTER_KEYS_HEX") {
+            let mut keys = HashMap::new();
+            for entry in spec.split(',') {
+                let Some((id_s, hex_s)) = entry.split_once(':') else { continue };
+                let Ok(id) = id_s.parse::<u32>() else { continue };
+                let Some(bytes) = decode_hex_32(hex_s) else { continue };
+                keys.insert(id, Key::from(bytes));
+            }
+            if let Some(active) = keys.keys().max().copied() {
+                return Self { active, keys };
+            }
+            tracing::warn!(
+                "SINYALIST_MASTER_KEYS_HEX is set but no entry parsed as `id:hex64`; generating an ephemeral key instead"
+            );
+        }
+        tracing::warn!(
+            "no SINYALIST_MASTER_KEYS_HEX set — generating an ephemeral at-rest encryption key; persisted records won't decrypt across restarts"
+        );
+        let mut bytes = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut bytes);
+        let mut keys = HashMap::new();
+        keys.insert(1, Key::from(bytes));
+        Self { active: 1, keys }
+    }
+
+    /// Seals `payload` under the active key, binding `aad` (the record's
+    /// `packet_id_hex` bytes) so the ciphertext only authenticates against
+    /// that one packet.
+    pub fn seal(&self, payload: &Payload, aad: &[u8]) -> SealedPayload {
+        let cipher = ChaCha20Poly1305::new(&self.keys[&self.active]);
+        let mut nonce_bytes = [0u8; 12];
+        rand::rngs::OsRng.fill_bytes(&mut nonce_bytes);
+        let plaintext = serde_json::to_vec(payload).expect("Payload always serializes");
+        let ciphertext = cipher
+            .encrypt(&Nonce::from(nonce_bytes), AeadPayload { msg: &plaintext, aad })
+            .expect("encryption under a freshly generated nonce cannot fail");
+        SealedPayload { key_id: self.active, nonce: nonce_bytes, ciphertext }
+    }
+
+    /// Decrypts `sealed`, re-checking the same `aad` passed to `seal`.
+    /// `None` covers a rotated-out/unknown key, a tampered ciphertext, and an
+    /// AAD mismatch alike — callers treat all three as "can't show this
+    /// record" rather than distinguishing why.
+    pub fn open(&self, sealed: &SealedPayload, aad: &[u8]) -> Option<Payload> {
+        let key = self.keys.get(&sealed.key_id)?;
+        let cipher = ChaCha20Poly1305::new(key);
+        let plaintext = cipher
+            .decrypt(&Nonce::from(sealed.nonce), AeadPayload { msg: &sealed.ciphertext, aad })
+            .ok()?;
+        serde_json::from_slice(&plaintext).ok()
+    }
+}
+
+fn decode_hex_32(s: &str) -> Option<[u8; 32]> {
+    if s.len() != 64 {
+        return None;
+    }
+    let mut out = [0u8; 32];
+    for (i, b) in out.iter_mut().enumerate() {
+        *b = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_payload() -> Payload {
+        Payload {
+            user_id: 42,
+            lat_e7: 410_000_000,
+            lon_e7: 290_000_000,
+            created_at_ms: 1000,
+            msg_type: 0,
+            pubkey_hex: "ab".repeat(32),
+            room_hint: "3B".to_string(),
+            blood_type: 7,
+            pulse_bpm: 88,
+            spo2_percent: 97,
+        }
+    }
+
+    #[test]
+    fn test_seal_then_open_roundtrips() {
+        let ring = KeyRing::load_or_generate();
+        let payload = sample_payload();
+        let sealed = ring.seal(&payload, b"packet-1");
+        assert_eq!(ring.open(&sealed, b"packet-1"), Some(payload));
+    }
+
+    #[test]
+    fn test_open_rejects_wrong_aad() {
+        let ring = KeyRing::load_or_generate();
+        let sealed = ring.seal(&sample_payload(), b"packet-1");
+        assert_eq!(ring.open(&sealed, b"packet-2"), None);
+    }
+
+    #[test]
+    fn test_open_rejects_tampered_ciphertext() {
+        let ring = KeyRing::load_or_generate();
+        let mut sealed = ring.seal(&sample_payload(), b"packet-1");
+        let last = sealed.ciphertext.len() - 1;
+        sealed.ciphertext[last] ^= 0xff;
+        assert_eq!(ring.open(&sealed, b"packet-1"), None);
+    }
+
+    #[test]
+    fn test_rotation_keeps_old_key_decryptable() {
+        std::env::set_var("SINYALIST_MASTER_KEYS_HEX", format!("1:{}", "11".repeat(32)));
+        let old_ring = KeyRing::load_or_generate();
+        let sealed = old_ring.seal(&sample_payload(), b"packet-1");
+
+        std::env::set_var("SINYALIST_MASTER_KEYS_HEX", format!("1:{},2:{}", "11".repeat(32), "22".repeat(32)));
+        let rotated_ring = KeyRing::load_or_generate();
+        assert_eq!(rotated_ring.active, 2);
+        assert_eq!(rotated_ring.open(&sealed, b"packet-1"), Some(sample_payload()));
+
+        std::env::remove_var("SINYALIST_MASTER_KEYS_HEX");
+    }
+}
@@ -0,0 +1,188 @@
+// =============================================================================
+// SINYALIST — embedded LMDB storage backend
+// =============================================================================
+// LMDB keys are sorted lexicographically by byte order, so range queries
+// come for free from choosing big-endian composite keys — no secondary index
+// structure needed beyond one extra named database per dimension a query
+// method filters on (`by_geo`, `by_time`, `by_trapped`), each mapping its key
+// to the record's id in `records`.
+// =============================================================================
+
+use super::{PacketRecord, PersistBackend};
+use heed::byteorder::BigEndian;
+use heed::types::{Bytes, SerdeJson, Unit, U64};
+use heed::{Database, Env, EnvOpenOptions, RoTxn};
+use std::ops::Bound;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+type BU64 = U64<BigEndian>;
+
+pub struct LmdbBackend {
+    env: Env,
+    records: Database<BU64, SerdeJson<PacketRecord>>,
+    by_geo: Database<Bytes, Unit>,
+    by_time: Database<Bytes, Unit>,
+    by_trapped: Database<Bytes, Unit>,
+    next_id: AtomicU64,
+    // LMDB allows only one writer at a time anyway; serializing `append`
+    // calls here keeps id assignment and the write txn consistent without
+    // relying on `Env::write_txn()`'s own blocking to be enough on its own.
+    write_lock: Mutex<()>,
+}
+
+impl LmdbBackend {
+    pub fn open(base_path: &str) -> heed::Result<Self> {
+        let dir = format!("{base_path}_lmdb");
+        std::fs::create_dir_all(&dir)?;
+        let env = unsafe {
+            EnvOpenOptions::new()
+                .map_size(1 << 30) // reserves address space, not disk, up front
+                .max_dbs(4)
+                .open(&dir)?
+        };
+        let mut wtxn = env.write_txn()?;
+        let records = env.create_database(&mut wtxn, Some("records"))?;
+        let by_geo = env.create_database(&mut wtxn, Some("by_geo"))?;
+        let by_time = env.create_database(&mut wtxn, Some("by_time"))?;
+        let by_trapped = env.create_database(&mut wtxn, Some("by_trapped"))?;
+        let next_id = records.last(&wtxn)?.map(|(id, _)| id + 1).unwrap_or(0);
+        wtxn.commit()?;
+        Ok(Self { env, records, by_geo, by_time, by_trapped, next_id: AtomicU64::new(next_id), write_lock: Mutex::new(()) })
+    }
+
+    fn geo_key_bytes(geo_key: u64, time_bucket: u64, id: u64) -> Vec<u8> {
+        let mut k = Vec::with_capacity(24);
+        k.extend_from_slice(&geo_key.to_be_bytes());
+        k.extend_from_slice(&time_bucket.to_be_bytes());
+        k.extend_from_slice(&id.to_be_bytes());
+        k
+    }
+
+    fn time_key_bytes(timestamp_ms: u64, id: u64) -> Vec<u8> {
+        let mut k = Vec::with_capacity(16);
+        k.extend_from_slice(&timestamp_ms.to_be_bytes());
+        k.extend_from_slice(&id.to_be_bytes());
+        k
+    }
+
+    fn trapped_key_bytes(is_trapped: bool, timestamp_ms: u64, id: u64) -> Vec<u8> {
+        let mut k = Vec::with_capacity(17);
+        k.push(is_trapped as u8);
+        k.extend_from_slice(&timestamp_ms.to_be_bytes());
+        k.extend_from_slice(&id.to_be_bytes());
+        k
+    }
+
+    fn lookup(&self, rtxn: &RoTxn, id_bytes: &[u8]) -> Option<PacketRecord> {
+        let id = u64::from_be_bytes(id_bytes.try_into().ok()?);
+        self.records.get(rtxn, &id).ok().flatten()
+    }
+}
+
+fn to_io_err<E: std::fmt::Display>(e: E) -> std::io::Error {
+    std::io::Error::other(e.to_string())
+}
+
+impl PersistBackend for LmdbBackend {
+    fn append(&self, batch: &[PacketRecord]) -> std::io::Result<()> {
+        let _g = self.write_lock.lock().unwrap();
+        let mut wtxn = self.env.write_txn().map_err(to_io_err)?;
+        for r in batch {
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+            self.records.put(&mut wtxn, &id, r).map_err(to_io_err)?;
+            self.by_geo.put(&mut wtxn, &Self::geo_key_bytes(r.geo_key, r.time_bucket, id), &()).map_err(to_io_err)?;
+            self.by_time.put(&mut wtxn, &Self::time_key_bytes(r.timestamp_ms, id), &()).map_err(to_io_err)?;
+            self.by_trapped.put(&mut wtxn, &Self::trapped_key_bytes(r.is_trapped, r.timestamp_ms, id), &()).map_err(to_io_err)?;
+        }
+        wtxn.commit().map_err(to_io_err)
+    }
+
+    fn by_geo_cell(&self, geo_key: u64, time_bucket: u64) -> Vec<PacketRecord> {
+        let Ok(rtxn) = self.env.read_txn() else { return Vec::new() };
+        let mut prefix = Vec::with_capacity(16);
+        prefix.extend_from_slice(&geo_key.to_be_bytes());
+        prefix.extend_from_slice(&time_bucket.to_be_bytes());
+        let Ok(iter) = self.by_geo.prefix_iter(&rtxn, &prefix) else { return Vec::new() };
+        iter.filter_map(|e| e.ok())
+            .filter_map(|(k, _)| self.lookup(&rtxn, &k[16..24]))
+            .collect()
+    }
+
+    fn by_time_range(&self, start_ms: u64, end_ms: u64) -> Vec<PacketRecord> {
+        let Ok(rtxn) = self.env.read_txn() else { return Vec::new() };
+        let lo = Self::time_key_bytes(start_ms, 0);
+        let hi = Self::time_key_bytes(end_ms, 0);
+        let range = (Bound::Included(lo.as_slice()), Bound::Excluded(hi.as_slice()));
+        let Ok(iter) = self.by_time.range(&rtxn, &range) else { return Vec::new() };
+        iter.filter_map(|e| e.ok())
+            .filter_map(|(k, _)| self.lookup(&rtxn, &k[8..16]))
+            .collect()
+    }
+
+    fn trapped_since(&self, since_ms: u64) -> Vec<PacketRecord> {
+        let Ok(rtxn) = self.env.read_txn() else { return Vec::new() };
+        let lo = Self::trapped_key_bytes(true, since_ms, 0);
+        let hi = Self::trapped_key_bytes(true, u64::MAX, u64::MAX);
+        let range = (Bound::Included(lo.as_slice()), Bound::Included(hi.as_slice()));
+        let Ok(iter) = self.by_trapped.range(&rtxn, &range) else { return Vec::new() };
+        iter.filter_map(|e| e.ok())
+            .filter_map(|(k, _)| self.lookup(&rtxn, &k[9..17]))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::{sample, scratch_base_path};
+    use crate::storage::KeyRing;
+
+    #[test]
+    fn test_append_and_by_geo_cell() {
+        let k = KeyRing::load_or_generate();
+        let b = LmdbBackend::open(&scratch_base_path("lmdb_geo")).unwrap();
+        b.append(&[sample(&k, 1, 1, 1000, false), sample(&k, 1, 1, 2000, false), sample(&k, 2, 1, 1000, false)]).unwrap();
+        assert_eq!(b.by_geo_cell(1, 1).len(), 2);
+        assert_eq!(b.by_geo_cell(2, 1).len(), 1);
+        assert_eq!(b.by_geo_cell(9, 9).len(), 0);
+    }
+
+    #[test]
+    fn test_by_time_range_is_half_open() {
+        let k = KeyRing::load_or_generate();
+        let b = LmdbBackend::open(&scratch_base_path("lmdb_time")).unwrap();
+        b.append(&[sample(&k, 1, 1, 1000, false), sample(&k, 1, 1, 2000, false)]).unwrap();
+        assert_eq!(b.by_time_range(1000, 2000).len(), 1, "end_ms must be exclusive");
+        assert_eq!(b.by_time_range(1000, 2001).len(), 2);
+    }
+
+    #[test]
+    fn test_trapped_since_filters_non_trapped() {
+        let k = KeyRing::load_or_generate();
+        let b = LmdbBackend::open(&scratch_base_path("lmdb_trapped")).unwrap();
+        b.append(&[sample(&k, 1, 1, 1000, true), sample(&k, 1, 1, 1000, false), sample(&k, 1, 1, 500, true)]).unwrap();
+        assert_eq!(b.trapped_since(1000).len(), 1);
+    }
+
+    #[test]
+    fn test_ids_persist_across_instances() {
+        let k = KeyRing::load_or_generate();
+        let path = scratch_base_path("lmdb_durable");
+        LmdbBackend::open(&path).unwrap().append(&[sample(&k, 1, 1, 1000, false)]).unwrap();
+        // Re-opening must pick up `next_id` from the existing `records` db,
+        // not restart at 0 and collide with what's already stored.
+        let b2 = LmdbBackend::open(&path).unwrap();
+        b2.append(&[sample(&k, 1, 1, 2000, false)]).unwrap();
+        assert_eq!(b2.by_geo_cell(1, 1).len(), 2);
+    }
+
+    #[test]
+    fn test_records_db_holds_sealed_payload_only() {
+        let k = KeyRing::load_or_generate();
+        let b = LmdbBackend::open(&scratch_base_path("lmdb_sealed")).unwrap();
+        b.append(&[sample(&k, 1, 1, 1000, false)]).unwrap();
+        let rec = &b.by_geo_cell(1, 1)[0];
+        assert_eq!(rec.open(&k).unwrap().lat_e7, 410_000_000);
+    }
+}
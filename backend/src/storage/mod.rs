@@ -0,0 +1,145 @@
+// =============================================================================
+// SINYALIST — pluggable persist backend
+// =============================================================================
+// NDJSON is write-only: there's no way to ask "all trapped reports in geo
+// cell X in the last 10 minutes" without reading and parsing the whole file.
+// `PersistBackend` abstracts over where persisted packets actually live —
+// append-only NDJSON (the original, always available), an embedded SQLite
+// file (indexed, good query latency), or embedded LMDB (ordered keys, good
+// range-scan latency) — selected at startup via `SINYALIST_STORAGE_BACKEND`,
+// the way Garage picks its data-store backend at config time rather than
+// compiling one in.
+//
+// Whichever backend is active, the Merkle log (merkle.rs) still hashes the
+// same canonical JSON bytes `flush()` always produced — which physical store
+// a record also lands in doesn't change what the tamper-evident log commits
+// to. That now includes the sealed payload envelope below: the log commits
+// to exactly what's on disk, ciphertext and all.
+//
+// PII and medical fields (precise lat/lon, pubkey, blood type, pulse, SpO2,
+// room hint — see `crypto::Payload`) are AEAD-sealed before a record ever
+// reaches a `PersistBackend`; only `packet_id_hex` and the coarse,
+// low-sensitivity fields (`geo_key`, `time_bucket`, `timestamp_ms`,
+// `is_trapped`, `alert_level`) stay in the clear.
+// =============================================================================
+
+pub mod crypto;
+pub mod ndjson;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "lmdb")]
+pub mod lmdb;
+
+pub use crypto::{KeyRing, Payload, SealedPayload};
+
+use serde::{Deserialize, Serialize};
+
+/// One persisted packet record. `packet_id_hex`/`geo_key`/`time_bucket`/
+/// `timestamp_ms`/`is_trapped`/`alert_level` are the fields every backend
+/// indexes or filters on, kept in the clear; everything else lives inside
+/// `sealed` (see `crypto::Payload`) and only comes back via `open()`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PacketRecord {
+    pub packet_id_hex: String,
+    pub geo_key: u64,
+    pub time_bucket: u64,
+    pub timestamp_ms: u64,
+    pub is_trapped: bool,
+    pub alert_level: i32,
+    pub sealed: SealedPayload,
+}
+
+impl PacketRecord {
+    /// Decrypts this record's PII/medical fields under `keyring`. `None` if
+    /// the sealing key has since rotated out, or the ciphertext no longer
+    /// authenticates against `packet_id_hex` — query/proof callers treat
+    /// either case as "can't show this record" rather than erroring.
+    pub fn open(&self, keyring: &KeyRing) -> Option<Payload> {
+        keyring.open(&self.sealed, self.packet_id_hex.as_bytes())
+    }
+}
+
+/// A place `persist_worker`'s flushed batches land, queryable by the
+/// dimensions a rescue dashboard actually asks about.
+pub trait PersistBackend: Send + Sync {
+    /// Appends a batch of already-accepted records. Called from the persist
+    /// worker's flush cycle with up to ~1000 records at a time.
+    fn append(&self, batch: &[PacketRecord]) -> std::io::Result<()>;
+
+    /// All records in `(geo_key, time_bucket)` — the same cell/bucket
+    /// `GeoCluster` scores confidence over.
+    fn by_geo_cell(&self, geo_key: u64, time_bucket: u64) -> Vec<PacketRecord>;
+
+    /// All records with `timestamp_ms` in `[start_ms, end_ms)`.
+    fn by_time_range(&self, start_ms: u64, end_ms: u64) -> Vec<PacketRecord>;
+
+    /// All `is_trapped` records with `timestamp_ms >= since_ms`.
+    fn trapped_since(&self, since_ms: u64) -> Vec<PacketRecord>;
+}
+
+/// Picks a backend from `SINYALIST_STORAGE_BACKEND` ("ndjson" | "sqlite" |
+/// "lmdb"), defaulting to NDJSON when unset so existing deployments don't
+/// need to change anything. Falls back to NDJSON (with a warning) if the
+/// requested backend isn't compiled in or fails to open — a query endpoint
+/// running in degraded mode beats a server that won't start.
+pub fn build_backend(base_path: &str) -> Box<dyn PersistBackend> {
+    let kind = std::env::var("SINYALIST_STORAGE_BACKEND").unwrap_or_else(|_| "ndjson".to_string());
+    match kind.as_str() {
+        #[cfg(feature = "sqlite")]
+        "sqlite" => match sqlite::SqliteBackend::open(base_path) {
+            Ok(b) => return Box::new(b),
+            Err(e) => tracing::warn!(error=%e, "sqlite_backend_open_failed, falling back to ndjson"),
+        },
+        #[cfg(not(feature = "sqlite"))]
+        "sqlite" => tracing::warn!("SINYALIST_STORAGE_BACKEND=sqlite but the `sqlite` feature isn't compiled in; falling back to ndjson"),
+        #[cfg(feature = "lmdb")]
+        "lmdb" => match lmdb::LmdbBackend::open(base_path) {
+            Ok(b) => return Box::new(b),
+            Err(e) => tracing::warn!(error=%e, "lmdb_backend_open_failed, falling back to ndjson"),
+        },
+        #[cfg(not(feature = "lmdb"))]
+        "lmdb" => tracing::warn!("SINYALIST_STORAGE_BACKEND=lmdb but the `lmdb` feature isn't compiled in; falling back to ndjson"),
+        "ndjson" => {}
+        other => tracing::warn!(backend=%other, "unknown SINYALIST_STORAGE_BACKEND, falling back to ndjson"),
+    }
+    Box::new(ndjson::NdjsonBackend::new(base_path))
+}
+
+/// Test-only helpers shared by every backend's own test module: a sample
+/// record builder (sealed under a caller-supplied `KeyRing`, same as
+/// `flush()` would) and a collision-free scratch path under the OS temp dir
+/// (no `tempfile` crate in this workspace, so a counter does the job).
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::{KeyRing, PacketRecord, Payload};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    pub fn sample(keyring: &KeyRing, geo_key: u64, time_bucket: u64, timestamp_ms: u64, is_trapped: bool) -> PacketRecord {
+        let payload = Payload {
+            user_id: 1,
+            lat_e7: 410_000_000,
+            lon_e7: 290_000_000,
+            created_at_ms: timestamp_ms,
+            msg_type: 0,
+            pubkey_hex: "ab".repeat(32),
+            room_hint: String::new(),
+            blood_type: 0,
+            pulse_bpm: 0,
+            spo2_percent: 0,
+        };
+        let packet_id_hex = "cd".repeat(16);
+        let sealed = keyring.seal(&payload, packet_id_hex.as_bytes());
+        PacketRecord { packet_id_hex, geo_key, time_bucket, timestamp_ms, is_trapped, alert_level: 0, sealed }
+    }
+
+    pub fn scratch_base_path(test_name: &str) -> String {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        // Process id in the path, not just the counter, so paths don't
+        // collide with leftovers from a previous `cargo test` run — the
+        // counter alone restarts at 0 every process and would otherwise
+        // reuse (and silently accumulate records in) the same scratch files.
+        let pid = std::process::id();
+        std::env::temp_dir().join(format!("sinyalist_test_{test_name}_{pid}_{n}")).to_string_lossy().into_owned()
+    }
+}
@@ -0,0 +1,113 @@
+use super::{PacketRecord, PersistBackend};
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::sync::Mutex;
+
+/// The original append-only NDJSON store. Always available (no extra crate
+/// deps, no schema to migrate), but every query is a full scan of the file —
+/// fine for small deployments or as the safe fallback, not for a dashboard
+/// polling a busy node.
+pub struct NdjsonBackend {
+    path: String,
+    // `append` and the read-side scans both touch the same file; a plain
+    // Mutex (not a DashMap-style sharded lock) is enough since there's only
+    // ever one underlying resource to serialize access to.
+    lock: Mutex<()>,
+}
+
+impl NdjsonBackend {
+    pub fn new(base_path: &str) -> Self {
+        Self { path: format!("{base_path}.ndjson"), lock: Mutex::new(()) }
+    }
+
+    fn read_all(&self) -> Vec<PacketRecord> {
+        let _g = self.lock.lock().unwrap();
+        let Ok(f) = std::fs::File::open(&self.path) else { return Vec::new() };
+        BufReader::new(f)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|l| serde_json::from_str(&l).ok())
+            .collect()
+    }
+}
+
+impl PersistBackend for NdjsonBackend {
+    fn append(&self, batch: &[PacketRecord]) -> std::io::Result<()> {
+        let _g = self.lock.lock().unwrap();
+        let mut f = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        for rec in batch {
+            if let Ok(line) = serde_json::to_string(rec) {
+                writeln!(f, "{line}")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn by_geo_cell(&self, geo_key: u64, time_bucket: u64) -> Vec<PacketRecord> {
+        self.read_all().into_iter().filter(|r| r.geo_key == geo_key && r.time_bucket == time_bucket).collect()
+    }
+
+    fn by_time_range(&self, start_ms: u64, end_ms: u64) -> Vec<PacketRecord> {
+        self.read_all().into_iter().filter(|r| r.timestamp_ms >= start_ms && r.timestamp_ms < end_ms).collect()
+    }
+
+    fn trapped_since(&self, since_ms: u64) -> Vec<PacketRecord> {
+        self.read_all().into_iter().filter(|r| r.is_trapped && r.timestamp_ms >= since_ms).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::{sample, scratch_base_path};
+    use crate::storage::KeyRing;
+
+    #[test]
+    fn test_append_and_by_geo_cell() {
+        let k = KeyRing::load_or_generate();
+        let b = NdjsonBackend::new(&scratch_base_path("ndjson_geo"));
+        b.append(&[sample(&k, 1, 1, 1000, false), sample(&k, 1, 1, 2000, false), sample(&k, 2, 1, 1000, false)]).unwrap();
+        assert_eq!(b.by_geo_cell(1, 1).len(), 2);
+        assert_eq!(b.by_geo_cell(2, 1).len(), 1);
+        assert_eq!(b.by_geo_cell(9, 9).len(), 0);
+    }
+
+    #[test]
+    fn test_by_time_range_is_half_open() {
+        let k = KeyRing::load_or_generate();
+        let b = NdjsonBackend::new(&scratch_base_path("ndjson_time"));
+        b.append(&[sample(&k, 1, 1, 1000, false), sample(&k, 1, 1, 2000, false)]).unwrap();
+        assert_eq!(b.by_time_range(1000, 2000).len(), 1, "end_ms must be exclusive");
+        assert_eq!(b.by_time_range(1000, 2001).len(), 2);
+    }
+
+    #[test]
+    fn test_trapped_since_filters_non_trapped() {
+        let k = KeyRing::load_or_generate();
+        let b = NdjsonBackend::new(&scratch_base_path("ndjson_trapped"));
+        b.append(&[sample(&k, 1, 1, 1000, true), sample(&k, 1, 1, 1000, false), sample(&k, 1, 1, 500, true)]).unwrap();
+        assert_eq!(b.trapped_since(1000).len(), 1);
+    }
+
+    #[test]
+    fn test_append_is_durable_across_instances() {
+        let k = KeyRing::load_or_generate();
+        let path = scratch_base_path("ndjson_durable");
+        NdjsonBackend::new(&path).append(&[sample(&k, 1, 1, 1000, false)]).unwrap();
+        // A fresh backend pointed at the same path sees what the first
+        // instance wrote — this is the NDJSON adapter's whole point.
+        assert_eq!(NdjsonBackend::new(&path).by_geo_cell(1, 1).len(), 1);
+    }
+
+    #[test]
+    fn test_sealed_payload_is_not_plaintext_on_disk() {
+        let k = KeyRing::load_or_generate();
+        let path = scratch_base_path("ndjson_sealed");
+        let b = NdjsonBackend::new(&path);
+        b.append(&[sample(&k, 1, 1, 1000, false)]).unwrap();
+        let on_disk = std::fs::read_to_string(format!("{path}.ndjson")).unwrap();
+        assert!(!on_disk.contains("410000000"), "lat_e7 must not appear in clear on disk");
+        let rec = &b.by_geo_cell(1, 1)[0];
+        assert_eq!(rec.open(&k).unwrap().lat_e7, 410_000_000);
+    }
+}
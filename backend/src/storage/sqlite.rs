@@ -0,0 +1,176 @@
+// =============================================================================
+// SINYALIST — embedded SQLite storage backend
+// =============================================================================
+// One `packets` table, indexed on `(geo_key, time_bucket)`, `(is_trapped,
+// timestamp_ms)`, and `timestamp_ms` alone — the three shapes
+// `PersistBackend`'s query methods actually run, so `persist_worker`'s
+// flushed batches become indexed rows instead of lines a dashboard has to
+// grep.
+// =============================================================================
+
+use super::{PacketRecord, PersistBackend, SealedPayload};
+use rusqlite::{params, Connection, Row};
+use std::sync::Mutex;
+
+pub struct SqliteBackend {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteBackend {
+    pub fn open(base_path: &str) -> rusqlite::Result<Self> {
+        let conn = Connection::open(format!("{base_path}.sqlite3"))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS packets (
+                id              INTEGER PRIMARY KEY AUTOINCREMENT,
+                packet_id_hex   TEXT NOT NULL,
+                geo_key         INTEGER NOT NULL,
+                time_bucket     INTEGER NOT NULL,
+                timestamp_ms    INTEGER NOT NULL,
+                is_trapped      INTEGER NOT NULL,
+                alert_level     INTEGER NOT NULL,
+                key_id          INTEGER NOT NULL,
+                nonce           BLOB NOT NULL,
+                ciphertext      BLOB NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_packets_geo_cell ON packets(geo_key, time_bucket);
+            CREATE INDEX IF NOT EXISTS idx_packets_trapped ON packets(is_trapped, timestamp_ms);
+            CREATE INDEX IF NOT EXISTS idx_packets_time ON packets(timestamp_ms);",
+        )?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn row_to_record(row: &Row) -> rusqlite::Result<PacketRecord> {
+        let nonce: Vec<u8> = row.get(8)?;
+        Ok(PacketRecord {
+            packet_id_hex: row.get(1)?,
+            geo_key: row.get::<_, i64>(2)? as u64,
+            time_bucket: row.get::<_, i64>(3)? as u64,
+            timestamp_ms: row.get::<_, i64>(4)? as u64,
+            is_trapped: row.get::<_, i64>(5)? != 0,
+            alert_level: row.get(6)?,
+            sealed: SealedPayload {
+                key_id: row.get(7)?,
+                nonce: nonce.try_into().unwrap_or([0u8; 12]),
+                ciphertext: row.get(9)?,
+            },
+        })
+    }
+
+    fn query(&self, sql: &str, params: &[&dyn rusqlite::ToSql]) -> Vec<PacketRecord> {
+        let conn = self.conn.lock().unwrap();
+        let Ok(mut stmt) = conn.prepare(sql) else { return Vec::new() };
+        let Ok(rows) = stmt.query_map(params, Self::row_to_record) else { return Vec::new() };
+        rows.filter_map(|r| r.ok()).collect()
+    }
+}
+
+fn to_io_err(e: rusqlite::Error) -> std::io::Error {
+    std::io::Error::other(e.to_string())
+}
+
+impl PersistBackend for SqliteBackend {
+    fn append(&self, batch: &[PacketRecord]) -> std::io::Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(to_io_err)?;
+        {
+            let mut stmt = tx
+                .prepare_cached(
+                    "INSERT INTO packets
+                     (packet_id_hex, geo_key, time_bucket, timestamp_ms, is_trapped, alert_level, key_id, nonce, ciphertext)
+                     VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9)",
+                )
+                .map_err(to_io_err)?;
+            for r in batch {
+                stmt.execute(params![
+                    r.packet_id_hex, r.geo_key as i64, r.time_bucket as i64, r.timestamp_ms as i64,
+                    r.is_trapped as i64, r.alert_level, r.sealed.key_id, r.sealed.nonce.as_slice(),
+                    r.sealed.ciphertext,
+                ])
+                .map_err(to_io_err)?;
+            }
+        }
+        tx.commit().map_err(to_io_err)
+    }
+
+    fn by_geo_cell(&self, geo_key: u64, time_bucket: u64) -> Vec<PacketRecord> {
+        self.query(
+            "SELECT * FROM packets WHERE geo_key = ?1 AND time_bucket = ?2",
+            params![geo_key as i64, time_bucket as i64],
+        )
+    }
+
+    fn by_time_range(&self, start_ms: u64, end_ms: u64) -> Vec<PacketRecord> {
+        self.query(
+            "SELECT * FROM packets WHERE timestamp_ms >= ?1 AND timestamp_ms < ?2",
+            params![start_ms as i64, end_ms as i64],
+        )
+    }
+
+    fn trapped_since(&self, since_ms: u64) -> Vec<PacketRecord> {
+        self.query(
+            "SELECT * FROM packets WHERE is_trapped = 1 AND timestamp_ms >= ?1",
+            params![since_ms as i64],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::{sample, scratch_base_path};
+    use crate::storage::KeyRing;
+
+    #[test]
+    fn test_append_and_by_geo_cell() {
+        let k = KeyRing::load_or_generate();
+        let b = SqliteBackend::open(&scratch_base_path("sqlite_geo")).unwrap();
+        b.append(&[sample(&k, 1, 1, 1000, false), sample(&k, 1, 1, 2000, false), sample(&k, 2, 1, 1000, false)]).unwrap();
+        assert_eq!(b.by_geo_cell(1, 1).len(), 2);
+        assert_eq!(b.by_geo_cell(2, 1).len(), 1);
+        assert_eq!(b.by_geo_cell(9, 9).len(), 0);
+    }
+
+    #[test]
+    fn test_by_time_range_is_half_open() {
+        let k = KeyRing::load_or_generate();
+        let b = SqliteBackend::open(&scratch_base_path("sqlite_time")).unwrap();
+        b.append(&[sample(&k, 1, 1, 1000, false), sample(&k, 1, 1, 2000, false)]).unwrap();
+        assert_eq!(b.by_time_range(1000, 2000).len(), 1, "end_ms must be exclusive");
+        assert_eq!(b.by_time_range(1000, 2001).len(), 2);
+    }
+
+    #[test]
+    fn test_trapped_since_filters_non_trapped() {
+        let k = KeyRing::load_or_generate();
+        let b = SqliteBackend::open(&scratch_base_path("sqlite_trapped")).unwrap();
+        b.append(&[sample(&k, 1, 1, 1000, true), sample(&k, 1, 1, 1000, false), sample(&k, 1, 1, 500, true)]).unwrap();
+        assert_eq!(b.trapped_since(1000).len(), 1);
+    }
+
+    #[test]
+    fn test_roundtrips_through_encryption() {
+        let k = KeyRing::load_or_generate();
+        let b = SqliteBackend::open(&scratch_base_path("sqlite_roundtrip")).unwrap();
+        let rec = sample(&k, 7, 3, 42, true);
+        b.append(std::slice::from_ref(&rec)).unwrap();
+        let got = &b.by_geo_cell(7, 3)[0];
+        assert_eq!(got.packet_id_hex, rec.packet_id_hex);
+        assert_eq!(got.is_trapped, rec.is_trapped);
+        let payload = got.open(&k).unwrap();
+        let original = rec.open(&k).unwrap();
+        assert_eq!(payload.user_id, original.user_id);
+        assert_eq!(payload.pubkey_hex, original.pubkey_hex);
+    }
+
+    #[test]
+    fn test_sealed_payload_is_not_plaintext_in_table() {
+        let k = KeyRing::load_or_generate();
+        let path = scratch_base_path("sqlite_sealed");
+        let b = SqliteBackend::open(&path).unwrap();
+        b.append(&[sample(&k, 1, 1, 1000, false)]).unwrap();
+        let conn = Connection::open(format!("{path}.sqlite3")).unwrap();
+        let has_pubkey_column: rusqlite::Result<i64> =
+            conn.query_row("SELECT COUNT(*) FROM pragma_table_info('packets') WHERE name = 'pubkey_hex'", [], |r| r.get(0));
+        assert_eq!(has_pubkey_column.unwrap(), 0, "pubkey must not have its own plaintext column");
+    }
+}
@@ -0,0 +1,236 @@
+// =============================================================================
+// SINYALIST — live cluster subscriptions for AFAD dashboards (C12)
+// =============================================================================
+// Before this, the only way to observe a confirmed cluster was to tail logs
+// or poll `/metrics` — `afad_worker` just logs each routed packet. `GET
+// /v1/subscribe` opens a Server-Sent Events stream instead: the caller gets
+// a snapshot of every currently-matching `GeoCluster`, then one event per
+// subsequent update, optionally narrowed to a geo bounding box and/or a
+// minimum alert level.
+//
+// Modeled on rs-matter's subscribe/dataversion mechanism: every `GeoCluster`
+// carries a monotonically increasing `version`, bumped in the same mutation
+// that updates `weighted_total`/`max_alert_level` (see `main.rs`'s ingest
+// handler and `gossip::merge_delta`). `Hub` fans those updates out over a
+// `tokio::sync::broadcast` channel — a reconnecting client just replays the
+// snapshot (current version per cluster) rather than needing any replayed
+// history, since only the latest version per `(geo_key, time_bucket)` is
+// ever meaningful.
+// =============================================================================
+
+use crate::{decode_geo_key, GeoCluster, CONSENSUS_WEIGHT_THRESHOLD, GEO_CELL_SIZE_E7};
+use axum::response::sse::Event;
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+
+// Small relative to `known_keys`/`clusters` capacity — this only needs to
+// cover the gap between "a cluster changed" and "every connected dashboard
+// has read that one event", not hold any real history. A lagging subscriber
+// just misses intermediate versions, which is fine (see `Hub::subscribe`).
+const CHANNEL_CAPACITY: usize = 4096;
+
+/// One `GeoCluster` mutation, broadcast to every subscriber whose filter
+/// matches it.
+#[derive(Clone, Serialize)]
+pub struct ClusterUpdate {
+    pub geo_key: u64,
+    pub time_bucket: u64,
+    pub version: u64,
+    pub confidence: f32,
+    pub weighted_total: f32,
+    pub consensus_reached: bool,
+    pub max_alert_level: i32,
+    // C15: unique trusted vs. untrusted reporters — see `trust::TrustPolicy`.
+    // Only `trusted_reporters` feeds `weighted_total`/consensus;
+    // `untrusted_reporters` is surfaced so an operator can see what's being
+    // filtered out instead of it silently disappearing from the cluster.
+    pub trusted_reporters: u32,
+    pub untrusted_reporters: u32,
+}
+
+impl ClusterUpdate {
+    pub fn from_cluster(geo_key: u64, time_bucket: u64, c: &GeoCluster) -> Self {
+        Self {
+            geo_key,
+            time_bucket,
+            version: c.version,
+            confidence: c.confidence(),
+            weighted_total: c.weighted_total,
+            consensus_reached: c.weighted_total >= CONSENSUS_WEIGHT_THRESHOLD,
+            max_alert_level: c.max_alert_level,
+            trusted_reporters: c.keys.len() as u32,
+            untrusted_reporters: c.untrusted_keys.len() as u32,
+        }
+    }
+}
+
+/// Query params for `GET /v1/subscribe` — every field optional, unset means
+/// "don't filter on this dimension."
+#[derive(Deserialize, Clone)]
+pub struct SubscribeQuery {
+    pub min_lat_e7: Option<i32>,
+    pub max_lat_e7: Option<i32>,
+    pub min_lon_e7: Option<i32>,
+    pub max_lon_e7: Option<i32>,
+    pub min_alert_level: Option<i32>,
+}
+
+impl SubscribeQuery {
+    /// True if `u`'s cluster overlaps the requested bounding box (if any)
+    /// and meets the requested minimum alert level (if any).
+    pub fn matches(&self, u: &ClusterUpdate) -> bool {
+        if let Some(min) = self.min_alert_level {
+            if u.max_alert_level < min {
+                return false;
+            }
+        }
+        let has_bbox = self.min_lat_e7.is_some() || self.max_lat_e7.is_some()
+            || self.min_lon_e7.is_some() || self.max_lon_e7.is_some();
+        if has_bbox {
+            let (lat_lo, lon_lo) = decode_geo_key(u.geo_key);
+            let lat_hi = lat_lo + GEO_CELL_SIZE_E7;
+            let lon_hi = lon_lo + GEO_CELL_SIZE_E7;
+            if let Some(min_lat) = self.min_lat_e7 {
+                if lat_hi < min_lat { return false; }
+            }
+            if let Some(max_lat) = self.max_lat_e7 {
+                if lat_lo > max_lat { return false; }
+            }
+            if let Some(min_lon) = self.min_lon_e7 {
+                if lon_hi < min_lon { return false; }
+            }
+            if let Some(max_lon) = self.max_lon_e7 {
+                if lon_lo > max_lon { return false; }
+            }
+        }
+        true
+    }
+}
+
+/// Fans `ClusterUpdate`s out to every connected `/v1/subscribe` client.
+pub struct Hub {
+    tx: broadcast::Sender<ClusterUpdate>,
+}
+
+impl Hub {
+    pub fn new() -> Self {
+        let (tx, _rx) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { tx }
+    }
+
+    /// Broadcasts `update`. No subscribers is the common case (most
+    /// clusters never get a dashboard watching them) and isn't an error.
+    pub fn publish(&self, update: ClusterUpdate) {
+        let _ = self.tx.send(update);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ClusterUpdate> {
+        self.tx.subscribe()
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+}
+
+impl Default for Hub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn to_event(u: &ClusterUpdate) -> Event {
+    Event::default().event("cluster_update").json_data(u).unwrap_or_else(|_| Event::default())
+}
+
+/// A snapshot of every currently-matching cluster, followed by live updates
+/// from `rx` that match `query` — the SSE body for one `/v1/subscribe`
+/// connection. A receiver that falls behind (`BroadcastStreamRecvError::
+/// Lagged`) just skips the versions it missed; the next one it does see is
+/// still self-consistent, so there's nothing to recover.
+pub fn event_stream(
+    snapshot: Vec<ClusterUpdate>,
+    rx: broadcast::Receiver<ClusterUpdate>,
+    query: SubscribeQuery,
+) -> impl Stream<Item = Result<Event, std::convert::Infallible>> {
+    let initial = stream::iter(snapshot.into_iter().map(|u| Ok(to_event(&u))));
+    let live = BroadcastStream::new(rx)
+        .filter_map(|r| async move { r.ok() })
+        .filter(move |u| std::future::ready(query.matches(u)))
+        .map(|u| Ok(to_event(&u)));
+    initial.chain(live)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(geo_key: u64, max_alert_level: i32) -> ClusterUpdate {
+        ClusterUpdate {
+            geo_key,
+            time_bucket: 1,
+            version: 1,
+            confidence: 0.5,
+            weighted_total: 1.0,
+            consensus_reached: false,
+            max_alert_level,
+            trusted_reporters: 1,
+            untrusted_reporters: 0,
+        }
+    }
+
+    #[test]
+    fn test_empty_query_matches_everything() {
+        let q = SubscribeQuery { min_lat_e7: None, max_lat_e7: None, min_lon_e7: None, max_lon_e7: None, min_alert_level: None };
+        assert!(q.matches(&update(crate::geo_key(410_000_000, 290_000_000), 0)));
+    }
+
+    #[test]
+    fn test_min_alert_level_filters_below_threshold() {
+        let q = SubscribeQuery { min_lat_e7: None, max_lat_e7: None, min_lon_e7: None, max_lon_e7: None, min_alert_level: Some(3) };
+        assert!(!q.matches(&update(1, 2)));
+        assert!(q.matches(&update(1, 3)));
+    }
+
+    #[test]
+    fn test_bbox_includes_overlapping_cell() {
+        let gk = crate::geo_key(410_000_000, 290_000_000);
+        let q = SubscribeQuery {
+            min_lat_e7: Some(409_000_000), max_lat_e7: Some(411_000_000),
+            min_lon_e7: Some(289_000_000), max_lon_e7: Some(291_000_000),
+            min_alert_level: None,
+        };
+        assert!(q.matches(&update(gk, 0)));
+    }
+
+    #[test]
+    fn test_bbox_excludes_far_cell() {
+        let gk = crate::geo_key(410_000_000, 290_000_000);
+        let q = SubscribeQuery {
+            min_lat_e7: Some(500_000_000), max_lat_e7: Some(501_000_000),
+            min_lon_e7: None, max_lon_e7: None,
+            min_alert_level: None,
+        };
+        assert!(!q.matches(&update(gk, 0)));
+    }
+
+    #[test]
+    fn test_hub_publish_without_subscribers_does_not_error() {
+        let hub = Hub::new();
+        hub.publish(update(1, 0));
+        assert_eq!(hub.subscriber_count(), 0);
+    }
+
+    #[test]
+    fn test_hub_subscriber_receives_published_update() {
+        let hub = Hub::new();
+        let mut rx = hub.subscribe();
+        assert_eq!(hub.subscriber_count(), 1);
+        hub.publish(update(7, 2));
+        let got = rx.try_recv().unwrap();
+        assert_eq!(got.geo_key, 7);
+        assert_eq!(got.max_alert_level, 2);
+    }
+}
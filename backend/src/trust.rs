@@ -0,0 +1,183 @@
+// =============================================================================
+// SINYALIST — trusted-key modes for Sybil-resistant consensus (C15)
+// =============================================================================
+// `GeoCluster.keys`/`weighted_total` and the consensus threshold they feed
+// treat any well-formed Ed25519 key as a distinct reporter — fine against
+// honest noise, but an attacker who can mint keypairs for free can mint
+// consensus too. `TrustPolicy` (borrowing the two trust models VpnCloud
+// offers its mesh peers) restricts which keys actually count as unique
+// reporters toward `GeoCluster.keys`:
+//
+//   - `Open` (default): every key counts, exactly as before this chunk — no
+//     deployment-time trust material required, so existing single-operator
+//     deployments are unaffected.
+//   - `Allowlist`: only keys the operator has explicitly loaded are trusted.
+//     An untrusted key's packet is still accepted and stored (so the
+//     operator can see what's being rejected from consensus), just excluded
+//     from `GeoCluster.keys`/`weighted_total` and acked `accepted_unverified`
+//     instead of `accepted`.
+//   - `SharedSecret`: every device in a trusted cohort is provisioned with
+//     the *same* Ed25519 keypair, deterministically derived from one shared
+//     secret the operator distributes out of band — the server only ever
+//     needs to know the resulting single public key, never the secret's
+//     holders individually.
+//
+// Configured via `SINYALIST_TRUST_MODE=open|allowlist|shared_secret`
+// (default `open`), `SINYALIST_TRUST_ALLOWLIST_HEX` (comma-separated
+// 64-hex-char pubkeys, allowlist mode), `SINYALIST_TRUST_SHARED_SECRET_HEX`
+// (a 64-hex-char secret, shared_secret mode).
+// =============================================================================
+
+use std::collections::HashSet;
+
+pub enum TrustPolicy {
+    Open,
+    Allowlist(HashSet<[u8; 32]>),
+    SharedSecret([u8; 32]),
+}
+
+impl TrustPolicy {
+    /// Whether `key` counts as a trusted reporter under this policy.
+    pub fn is_trusted(&self, key: &[u8; 32]) -> bool {
+        match self {
+            TrustPolicy::Open => true,
+            TrustPolicy::Allowlist(keys) => keys.contains(key),
+            TrustPolicy::SharedSecret(expected) => key == expected,
+        }
+    }
+
+    /// Builds a policy from `SINYALIST_TRUST_*` env vars — see module doc.
+    /// Falls back to `Open` if the mode is unset, unrecognized, or its
+    /// required material is missing or malformed, so a misconfigured trust
+    /// policy fails open (every key counts) rather than silently excluding
+    /// every reporter from consensus.
+    pub fn from_env() -> Self {
+        match std::env::var("SINYALIST_TRUST_MODE").unwrap_or_default().as_str() {
+            "allowlist" => {
+                let keys: HashSet<[u8; 32]> = std::env::var("SINYALIST_TRUST_ALLOWLIST_HEX")
+                    .unwrap_or_default()
+                    .split(',')
+                    .filter_map(|s| decode_hex_32(s.trim()))
+                    .collect();
+                if keys.is_empty() {
+                    tracing::warn!(
+                        "SINYALIST_TRUST_MODE=allowlist but SINYALIST_TRUST_ALLOWLIST_HEX has no valid keys — falling back to open"
+                    );
+                    return TrustPolicy::Open;
+                }
+                TrustPolicy::Allowlist(keys)
+            }
+            "shared_secret" => {
+                let secret = std::env::var("SINYALIST_TRUST_SHARED_SECRET_HEX").ok().and_then(|s| decode_hex_32(s.trim()));
+                match secret {
+                    Some(secret) => TrustPolicy::SharedSecret(derive_shared_pubkey(&secret)),
+                    None => {
+                        tracing::warn!(
+                            "SINYALIST_TRUST_MODE=shared_secret but SINYALIST_TRUST_SHARED_SECRET_HEX is missing/malformed — falling back to open"
+                        );
+                        TrustPolicy::Open
+                    }
+                }
+            }
+            _ => TrustPolicy::Open,
+        }
+    }
+}
+
+/// The single public key every device provisioned with `secret` validates
+/// against — deterministic, so every node configured with the same secret
+/// arrives at the same expected key without any key exchange between them.
+fn derive_shared_pubkey(secret: &[u8; 32]) -> [u8; 32] {
+    ed25519_dalek::SigningKey::from_bytes(secret).verifying_key().to_bytes()
+}
+
+/// Shared with `main::gossip_secret_from_env` (C17) — both parse a
+/// 64-hex-char secret the same way.
+pub(crate) fn decode_hex_32(s: &str) -> Option<[u8; 32]> {
+    if s.len() != 64 {
+        return None;
+    }
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(out)
+}
+
+/// Constant-time equality for comparing a presented secret against the
+/// configured one (e.g. `main::gossip_exchange`'s `GOSSIP_SECRET_HEADER`
+/// check) — `==` on `[u8; 32]` short-circuits on the first differing byte,
+/// a timing side channel on a value whose entire purpose is authentication.
+/// Hand-rolled instead of pulling in `subtle`/`ring`: OR every byte's XOR
+/// together so the result depends on all 32 bytes regardless of where the
+/// first mismatch falls.
+pub(crate) fn constant_time_eq(a: &[u8; 32], b: &[u8; 32]) -> bool {
+    let mut diff = 0u8;
+    for i in 0..32 {
+        diff |= a[i] ^ b[i];
+    }
+    diff == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(n: u8) -> [u8; 32] {
+        let mut k = [0u8; 32];
+        k[0] = n;
+        k
+    }
+
+    #[test]
+    fn test_open_trusts_every_key() {
+        assert!(TrustPolicy::Open.is_trusted(&key(1)));
+        assert!(TrustPolicy::Open.is_trusted(&key(2)));
+    }
+
+    #[test]
+    fn test_allowlist_trusts_only_listed_keys() {
+        let p = TrustPolicy::Allowlist(HashSet::from([key(1)]));
+        assert!(p.is_trusted(&key(1)));
+        assert!(!p.is_trusted(&key(2)));
+    }
+
+    #[test]
+    fn test_shared_secret_trusts_only_the_derived_key() {
+        let secret = key(9);
+        let expected = derive_shared_pubkey(&secret);
+        let p = TrustPolicy::SharedSecret(expected);
+        assert!(p.is_trusted(&expected));
+        assert!(!p.is_trusted(&key(1)));
+    }
+
+    #[test]
+    fn test_shared_secret_derivation_is_deterministic() {
+        let secret = key(9);
+        assert_eq!(derive_shared_pubkey(&secret), derive_shared_pubkey(&secret));
+    }
+
+    #[test]
+    fn test_decode_hex_32_rejects_wrong_length() {
+        assert_eq!(decode_hex_32("deadbeef"), None);
+    }
+
+    #[test]
+    fn test_decode_hex_32_roundtrips() {
+        let k = key(7);
+        let hex: String = k.iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(decode_hex_32(&hex), Some(k));
+    }
+
+    #[test]
+    fn test_constant_time_eq_matches_and_rejects() {
+        assert!(constant_time_eq(&key(3), &key(3)));
+        assert!(!constant_time_eq(&key(3), &key(4)));
+        // Differ only in the last byte — the kind of case a short-circuiting
+        // `==` would take the longest to reject, which is exactly what this
+        // helper must not do any slower than a first-byte mismatch.
+        let mut almost = key(3);
+        almost[31] ^= 1;
+        assert!(!constant_time_eq(&key(3), &almost));
+    }
+}
@@ -0,0 +1,235 @@
+// =============================================================================
+// SINYALIST — TTL map with O(expired) background eviction
+// =============================================================================
+// `eviction()` used to scan every entry in `dedup`/`rl_key`/`rl_geo` with a
+// `DashMap::retain` once a minute — O(n) over the whole map on every tick,
+// and an expired entry could linger up to a minute past its TTL. `TtlMap`
+// pairs a `DashMap` (same concurrent reads/writes from request handlers as
+// before) with a small expiry index, so a background driver can
+// `tokio::time::sleep` until the next *actual* expiry and pop exactly the
+// entries that aged out — O(expired) per wakeup, not O(total).
+//
+// Every `insert`/`entry_and_bump` call re-stamps the entry's expiry. Rather
+// than leaving the old index entry for the same key to rot until
+// `evict_expired` happens to pop it, the map stores each entry's current
+// `(expiry, seq)` index key alongside its value, so re-stamping can remove
+// the stale index slot in the same operation — otherwise a single key
+// bumped repeatedly (e.g. `rl_key`/`rl_geo` re-stamped by every request,
+// including ones that end up rate-limited) would grow the shared `queue`
+// by one entry per *request* instead of one per *live key*, even though
+// `packet_id`/key-churn is fully attacker-chosen and dedup runs before rate
+// limiting. `evict_expired` still treats the map's stored expiry as
+// authoritative as a safety net: a popped index entry whose expiry no
+// longer matches what's in the map has been superseded by a racing
+// concurrent bump, so it's silently dropped rather than evicting a still-
+// live entry early.
+// =============================================================================
+
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use std::collections::BTreeMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+pub struct TtlMap<K, V> {
+    // Value, current expiry, and the `seq` half of this entry's current
+    // index key — so a re-stamp can find and remove its own stale index
+    // entry instead of leaving it for `evict_expired` to eventually pop.
+    map: DashMap<K, (V, u64, u64)>,
+    // Keyed by (expiry_ms, insertion seq) rather than just expiry_ms, since
+    // two entries can legitimately expire at the same millisecond.
+    queue: Mutex<BTreeMap<(u64, u64), K>>,
+    seq: AtomicU64,
+}
+
+impl<K, V> TtlMap<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    pub fn with_capacity(cap: usize) -> Self {
+        Self { map: DashMap::with_capacity(cap), queue: Mutex::new(BTreeMap::new()), seq: AtomicU64::new(0) }
+    }
+
+    pub fn contains_key(&self, k: &K) -> bool {
+        self.map.contains_key(k)
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Inserts `value` with a fresh `now_ms + ttl_ms` expiry, overwriting
+    /// any existing entry for `key`. Matches the fire-and-forget semantics
+    /// `DashMap::insert` had for the dedup map.
+    pub fn insert(&self, key: K, value: V, now_ms: u64, ttl_ms: u64) {
+        let expiry = now_ms + ttl_ms;
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+        let prev_index = self.map.insert(key.clone(), (value, expiry, seq)).map(|(_, e, s)| (e, s));
+        self.reindex(key, expiry, seq, prev_index);
+    }
+
+    /// Runs `f` against the entry for `key` (creating it via `default()` if
+    /// absent), then re-stamps its expiry to `now_ms + ttl_ms`. Matches the
+    /// `entry(..).or_insert(..)` + in-place mutation pattern the rate
+    /// limiters used — every call extends the entry's life, same as a
+    /// sliding-TTL cache.
+    pub fn entry_and_bump(
+        &self,
+        key: K,
+        now_ms: u64,
+        ttl_ms: u64,
+        default: impl FnOnce() -> V,
+        f: impl FnOnce(&mut V),
+    ) {
+        let expiry = now_ms + ttl_ms;
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+        let prev_index = match self.map.entry(key.clone()) {
+            Entry::Occupied(mut e) => {
+                let (v, old_expiry, old_seq) = e.get_mut();
+                f(v);
+                let prev = (*old_expiry, *old_seq);
+                *old_expiry = expiry;
+                *old_seq = seq;
+                Some(prev)
+            }
+            Entry::Vacant(e) => {
+                let mut v = default();
+                f(&mut v);
+                e.insert((v, expiry, seq));
+                None
+            }
+        };
+        self.reindex(key, expiry, seq, prev_index);
+    }
+
+    /// Inserts the new `(expiry, seq)` index entry for `key`, removing its
+    /// previous index entry (if any) in the same lock acquisition — so a
+    /// key that's bumped repeatedly (e.g. a rate limiter re-stamped on
+    /// every request) keeps exactly one live entry in `queue` instead of
+    /// growing it once per bump. `evict_expired`'s own staleness check
+    /// covers the remaining race where a concurrent bump on the same key
+    /// reorders relative to this one.
+    fn reindex(&self, key: K, expiry: u64, seq: u64, prev_index: Option<(u64, u64)>) {
+        let mut q = self.queue.lock().unwrap();
+        if let Some(prev) = prev_index {
+            q.remove(&prev);
+        }
+        q.insert((expiry, seq), key);
+    }
+
+    /// Pops every index entry whose expiry has passed `now_ms`, removing the
+    /// still-current ones from the map, and returns the evicted keys.
+    pub fn evict_expired(&self, now_ms: u64) -> Vec<K> {
+        let mut evicted = Vec::new();
+        let mut q = self.queue.lock().unwrap();
+        loop {
+            let Some((&(expiry, seq), _)) = q.iter().next() else { break };
+            if expiry > now_ms {
+                break;
+            }
+            let key = q.remove(&(expiry, seq)).unwrap();
+            // The map's stored expiry is authoritative. If it no longer
+            // matches this index entry, `key` was re-stamped by a later
+            // insert/bump — a fresher index entry for it is still pending,
+            // so this stale one is just dropped, not acted on.
+            let still_current = self.map.get(&key).map(|e| e.1 == expiry).unwrap_or(false);
+            if still_current {
+                self.map.remove(&key);
+                evicted.push(key);
+            }
+        }
+        evicted
+    }
+
+    /// The earliest pending expiry across all entries, for the background
+    /// driver to sleep until. `None` when nothing is queued.
+    pub fn next_expiry(&self) -> Option<u64> {
+        self.queue.lock().unwrap().keys().next().map(|&(expiry, _)| expiry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_contains() {
+        let m: TtlMap<Vec<u8>, ()> = TtlMap::with_capacity(4);
+        m.insert(vec![1], (), 0, 1000);
+        assert!(m.contains_key(&vec![1]));
+        assert!(!m.contains_key(&vec![2]));
+    }
+
+    #[test]
+    fn test_evict_expired_removes_only_aged_entries() {
+        let m: TtlMap<Vec<u8>, ()> = TtlMap::with_capacity(4);
+        m.insert(vec![1], (), 0, 1000); // expires at 1000
+        m.insert(vec![2], (), 0, 5000); // expires at 5000
+        let evicted = m.evict_expired(1000);
+        assert_eq!(evicted, vec![vec![1]]);
+        assert!(!m.contains_key(&vec![1]));
+        assert!(m.contains_key(&vec![2]), "entry not yet past its TTL must survive");
+    }
+
+    #[test]
+    fn test_evict_is_on_expiry_not_wall_clock_scan() {
+        let m: TtlMap<Vec<u8>, ()> = TtlMap::with_capacity(4);
+        m.insert(vec![1], (), 0, 100);
+        assert!(m.evict_expired(50).is_empty(), "must not evict before the TTL elapses");
+        assert_eq!(m.evict_expired(100), vec![vec![1]]);
+    }
+
+    #[test]
+    fn test_reinsert_bumps_expiry_and_removes_its_own_stale_index_entry() {
+        let m: TtlMap<Vec<u8>, ()> = TtlMap::with_capacity(4);
+        m.insert(vec![1], (), 0, 100); // expiry 100, removed below by the re-insert itself
+        m.insert(vec![1], (), 50, 100); // new expiry 150
+        assert_eq!(m.queue.lock().unwrap().len(), 1, "re-inserting a key must remove its own stale index entry, not just leave it for eviction to ignore");
+        assert!(m.evict_expired(100).is_empty(), "re-inserted key must not be evicted by its old expiry");
+        assert!(m.contains_key(&vec![1]));
+        assert_eq!(m.evict_expired(150), vec![vec![1]], "it should still expire at its current TTL");
+    }
+
+    #[test]
+    fn test_entry_and_bump_creates_and_mutates() {
+        let m: TtlMap<Vec<u8>, u32> = TtlMap::with_capacity(4);
+        m.entry_and_bump(vec![1], 0, 1000, || 0, |v| *v += 1);
+        m.entry_and_bump(vec![1], 0, 1000, || 0, |v| *v += 1);
+        assert_eq!(m.map.get(&vec![1]).unwrap().0, 2);
+    }
+
+    #[test]
+    fn test_repeated_bump_does_not_grow_the_index_per_call() {
+        // packet_id (and thus the dedup/rate-limit keys derived from it) is
+        // fully attacker-chosen, so a single key re-signed locally can call
+        // entry_and_bump an unbounded number of times — the index must stay
+        // O(live keys), not O(calls), or it becomes an uncapped-memory /
+        // single-mutex-contention vector.
+        let m: TtlMap<Vec<u8>, u32> = TtlMap::with_capacity(4);
+        for _ in 0..50 {
+            m.entry_and_bump(vec![1], 0, 1000, || 0, |v| *v += 1);
+        }
+        assert_eq!(m.queue.lock().unwrap().len(), 1, "repeatedly bumping one key must leave exactly one index entry, not one per call");
+        assert_eq!(m.map.get(&vec![1]).unwrap().0, 50);
+    }
+
+    #[test]
+    fn test_next_expiry_is_the_minimum() {
+        let m: TtlMap<Vec<u8>, ()> = TtlMap::with_capacity(4);
+        assert_eq!(m.next_expiry(), None);
+        m.insert(vec![1], (), 0, 5000);
+        m.insert(vec![2], (), 0, 1000);
+        assert_eq!(m.next_expiry(), Some(1000));
+    }
+
+    #[test]
+    fn test_len_tracks_live_entries() {
+        let m: TtlMap<Vec<u8>, ()> = TtlMap::with_capacity(4);
+        m.insert(vec![1], (), 0, 100);
+        m.insert(vec![2], (), 0, 200);
+        assert_eq!(m.len(), 2);
+        m.evict_expired(100);
+        assert_eq!(m.len(), 1);
+    }
+}
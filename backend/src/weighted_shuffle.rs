@@ -0,0 +1,113 @@
+// =============================================================================
+// SINYALIST — weighted-random reporter sampling (C17)
+// =============================================================================
+// `GeoCluster::confidence()` already sums per-key `reputation::weight()`
+// instead of counting keys (C11), but a cluster's `keys` set has no
+// meaningful order of its own — `HashSet` iteration order isn't even stable
+// run to run. Picking "the first N" reporters to show an operator or to fan
+// out to a rate-limited downstream would really just be picking N in
+// whatever order a `HashSet` happens to hash them.
+//
+// `weighted_shuffle` borrows Solana's gossip weighted-shuffle: given each
+// reporter's weight, draw a full ordering where, at each step, every
+// remaining reporter's odds of being drawn next are proportional to its
+// remaining weight — so a cluster's highest-reputation reporters tend to
+// sort first, but without deterministically always being first. Seeded from
+// the cluster's own `(geo_key, time_bucket)` (see `cluster_seed`), so the
+// same cluster always shuffles the same way rather than flapping between
+// requests.
+// =============================================================================
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Combines a cluster's identity into one seed for `weighted_shuffle` —
+/// same mixing trick as splitmix64's constant, just enough to keep
+/// `(geo_key, time_bucket)` pairs that differ in only one field from
+/// colliding into the same seed.
+pub fn cluster_seed(geo_key: u64, time_bucket: u64) -> u64 {
+    geo_key ^ time_bucket.wrapping_mul(0x9E3779B97F4A7C15)
+}
+
+/// Returns the indices of `weights` in weighted-random shuffled order:
+/// repeatedly draws the next index with probability proportional to its
+/// remaining weight, without replacement. Zero-or-negative-weight entries
+/// never get drawn. Deterministic for a given `seed`.
+pub fn weighted_shuffle(weights: &[f32], seed: u64) -> Vec<usize> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut remaining: Vec<(usize, f32)> = weights
+        .iter()
+        .copied()
+        .enumerate()
+        .filter(|(_, w)| *w > 0.0)
+        .collect();
+    let mut order = Vec::with_capacity(remaining.len());
+    while !remaining.is_empty() {
+        let total: f32 = remaining.iter().map(|(_, w)| w).sum();
+        let r: f32 = rng.gen_range(0.0..total);
+        let mut acc = 0.0;
+        let mut pick = remaining.len() - 1;
+        for (i, (_, w)) in remaining.iter().enumerate() {
+            acc += w;
+            if r < acc {
+                pick = i;
+                break;
+            }
+        }
+        let (idx, _) = remaining.remove(pick);
+        order.push(idx);
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weighted_shuffle_is_a_permutation_of_all_indices() {
+        let weights = vec![1.0, 2.0, 3.0, 4.0];
+        let order = weighted_shuffle(&weights, 42);
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_weighted_shuffle_is_deterministic_for_same_seed() {
+        let weights = vec![1.0, 5.0, 2.0, 8.0, 1.0];
+        let a = weighted_shuffle(&weights, 7);
+        let b = weighted_shuffle(&weights, 7);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_weighted_shuffle_excludes_zero_weight_entries() {
+        let weights = vec![1.0, 0.0, 2.0];
+        let order = weighted_shuffle(&weights, 1);
+        assert_eq!(order.len(), 2);
+        assert!(!order.contains(&1));
+    }
+
+    #[test]
+    fn test_heavier_weight_wins_first_place_more_often() {
+        let weights = vec![1.0, 99.0];
+        let mut heavy_first = 0;
+        for seed in 0..200u64 {
+            if weighted_shuffle(&weights, seed)[0] == 1 {
+                heavy_first += 1;
+            }
+        }
+        assert!(heavy_first > 150, "a 99x heavier reporter should lead the shuffle almost every time, got {heavy_first}/200");
+    }
+
+    #[test]
+    fn test_cluster_seed_differs_for_different_time_buckets() {
+        assert_ne!(cluster_seed(1, 1), cluster_seed(1, 2));
+    }
+
+    #[test]
+    fn test_cluster_seed_is_deterministic() {
+        assert_eq!(cluster_seed(123, 456), cluster_seed(123, 456));
+    }
+}
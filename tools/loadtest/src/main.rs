@@ -8,9 +8,11 @@
 use clap::Parser;
 use ed25519_dalek::{Signer, SigningKey};
 use prost::Message;
-use rand::rngs::OsRng;
-use rand::Rng;
-use std::sync::atomic::{AtomicU64, Ordering};
+use rand::rngs::{OsRng, StdRng};
+use rand::{Rng, SeedableRng};
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
@@ -42,6 +44,61 @@ pub mod proto {
         pub ed25519_signature: Vec<u8>,
         #[prost(bytes, tag = "29")]
         pub ed25519_public_key: Vec<u8>,
+        #[prost(bytes, tag = "36")]
+        pub merkle_root: Vec<u8>,
+        #[prost(bytes, tag = "37")]
+        pub merkle_signature: Vec<u8>,
+        #[prost(bytes, repeated, tag = "38")]
+        pub merkle_proof: Vec<Vec<u8>>,
+        #[prost(uint32, tag = "39")]
+        pub leaf_index: u32,
+    }
+}
+
+// Mirrors `merkle_batch` in backend/src/lib.rs: a complete binary Merkle tree
+// over a batch's leaf hashes, signed once at the root instead of signing each
+// packet independently. Duplicated locally (rather than depending on the
+// server crate) since this tool keeps its own minimal proto subset.
+mod merkle_batch {
+    use super::{Digest, Sha256};
+
+    pub type Hash = [u8; 32];
+
+    pub fn leaf_hash(signing_bytes: &[u8]) -> Hash {
+        let mut h = Sha256::new();
+        h.update([0x00]);
+        h.update(signing_bytes);
+        h.finalize().into()
+    }
+
+    fn node_hash(left: &Hash, right: &Hash) -> Hash {
+        let mut h = Sha256::new();
+        h.update([0x01]);
+        h.update(left);
+        h.update(right);
+        h.finalize().into()
+    }
+
+    pub fn build_tree(leaves: &[Hash]) -> (Hash, Vec<Vec<Hash>>) {
+        let n = leaves.len().max(1).next_power_of_two();
+        let mut level: Vec<Hash> = leaves.to_vec();
+        level.resize(n, [0u8; 32]);
+        let mut proofs: Vec<Vec<Hash>> = (0..leaves.len()).map(|_| Vec::new()).collect();
+        let mut indices: Vec<usize> = (0..leaves.len()).collect();
+
+        while level.len() > 1 {
+            let mut next = Vec::with_capacity(level.len() / 2);
+            for pair in level.chunks(2) {
+                next.push(node_hash(&pair[0], &pair[1]));
+            }
+            for (leaf_i, idx) in indices.iter_mut().enumerate() {
+                let sibling = level[*idx ^ 1];
+                proofs[leaf_i].push(sibling);
+                *idx /= 2;
+            }
+            level = next;
+        }
+        (level[0], proofs)
     }
 }
 
@@ -72,6 +129,136 @@ struct Args {
     /// Center longitude (e7)
     #[arg(long, default_value_t = 290000000)]
     lon: i32,
+
+    /// Merkle-batch N packets under a single Ed25519 signature instead of
+    /// signing each one independently (amortizes signing cost; see proto
+    /// fields merkle_root/merkle_signature/merkle_proof/leaf_index)
+    #[arg(long)]
+    batch: Option<usize>,
+
+    /// Adversarial mode: send randomly-mutated (byte-flipped, truncated,
+    /// corrupted-signature, ...) packets to exercise the server's rejection
+    /// paths instead of sending well-formed ones
+    #[arg(long)]
+    fuzz: bool,
+
+    /// RNG seed for --fuzz; reusing the same seed reproduces the same
+    /// sequence of mutations. Defaults to a random seed, printed at startup.
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Directory to save mutated payloads that triggered a 5xx or a timeout,
+    /// for deterministic replay
+    #[arg(long, default_value = "fuzz-corpus")]
+    corpus_dir: String,
+
+    /// Dump full latency histogram bucket counts (overall + per status
+    /// class) as CSV to this path, for comparing runs across server configs
+    #[arg(long)]
+    latency_csv: Option<String>,
+
+    /// Offset timestamp_ms/created_at_ms by this many milliseconds (can be
+    /// negative) to probe the server's freshness window. Ignored if
+    /// --skew-sweep is also passed. Only applies to the plain (non-batch,
+    /// non-fuzz) send path.
+    #[arg(long)]
+    clock_skew: Option<i64>,
+
+    /// Ramp the clock skew from -5 minutes to +5 minutes over the run
+    /// instead of a fixed --clock-skew, and report the inferred max
+    /// tolerated skew in each direction from which packets were accepted
+    #[arg(long)]
+    skew_sweep: bool,
+
+    /// Re-send a previously used packet_id for this fraction (0.0-1.0) of
+    /// requests, to probe the server's dedup window. Only applies to the
+    /// plain (non-batch, non-fuzz) send path.
+    #[arg(long)]
+    replay_fraction: Option<f64>,
+}
+
+/// `--skew-sweep` ramps the offset linearly from `-SKEW_SWEEP_MAX_MS` to
+/// `+SKEW_SWEEP_MAX_MS` over the run's duration.
+const SKEW_SWEEP_MAX_MS: i64 = 300_000;
+
+/// How many recently-sent packet_ids `--replay-fraction` can pick from.
+const REPLAY_RING_SIZE: usize = 256;
+
+/// Lock-free, log2-bucketed latency histogram (HDR-style): bucket `i` covers
+/// `[2^i - 1, 2^(i+1) - 1)` microseconds, so 26 buckets span microseconds up
+/// to ~67s. An exact running sum/count is kept alongside the buckets for the
+/// mean, since that doesn't need estimating.
+const LATENCY_BUCKETS: usize = 26;
+
+struct LatencyHistogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS],
+    count: AtomicU64,
+    sum_us: AtomicU64,
+    max_us: AtomicU64,
+}
+
+impl LatencyHistogram {
+    fn new() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            count: AtomicU64::new(0),
+            sum_us: AtomicU64::new(0),
+            max_us: AtomicU64::new(0),
+        }
+    }
+
+    fn record(&self, us: u64) {
+        self.buckets[Self::bucket_index(us)].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_us.fetch_add(us, Ordering::Relaxed);
+        self.max_us.fetch_max(us, Ordering::Relaxed);
+    }
+
+    fn bucket_index(us: u64) -> usize {
+        let bits = 64 - (us + 1).leading_zeros();
+        (bits as usize - 1).min(LATENCY_BUCKETS - 1)
+    }
+
+    fn bucket_upper_us(i: usize) -> u64 {
+        (1u64 << (i + 1)) - 1
+    }
+
+    fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    fn mean(&self) -> u64 {
+        let n = self.count();
+        if n == 0 {
+            0
+        } else {
+            self.sum_us.load(Ordering::Relaxed) / n
+        }
+    }
+
+    fn max(&self) -> u64 {
+        self.max_us.load(Ordering::Relaxed)
+    }
+
+    /// Smallest recorded value's bucket whose cumulative count reaches the
+    /// `p`-th fraction of all samples (e.g. `p = 0.99` for p99). Returns the
+    /// bucket's upper bound as the estimate, in keeping with HDR histograms'
+    /// bucketed (not exact) percentiles.
+    fn percentile(&self, p: f64) -> u64 {
+        let total = self.count();
+        if total == 0 {
+            return 0;
+        }
+        let target = ((p * total as f64).ceil() as u64).max(1);
+        let mut cum = 0u64;
+        for (i, bucket) in self.buckets.iter().enumerate() {
+            cum += bucket.load(Ordering::Relaxed);
+            if cum >= target {
+                return Self::bucket_upper_us(i);
+            }
+        }
+        self.max()
+    }
 }
 
 struct Counters {
@@ -81,7 +268,26 @@ struct Counters {
     rate_limited: AtomicU64,
     queue_full: AtomicU64,
     network_error: AtomicU64,
-    latency_sum_us: AtomicU64,
+    latency_overall: LatencyHistogram,
+    latency_accepted: LatencyHistogram,
+    latency_rejected: LatencyHistogram,
+    latency_rate_limited: LatencyHistogram,
+    latency_queue_full: LatencyHistogram,
+    latency_other: LatencyHistogram,
+    batches_sent: AtomicU64,
+    batch_sign_us: AtomicU64,
+    fuzz_clean_reject: AtomicU64,
+    fuzz_parse_error: AtomicU64,
+    fuzz_server_error: AtomicU64,
+    fuzz_timeout: AtomicU64,
+    skew_sent: AtomicU64,
+    skew_accepted: AtomicU64,
+    skew_rejected: AtomicU64,
+    max_accepted_skew_ms: AtomicI64,
+    min_accepted_skew_ms: AtomicI64,
+    replay_sent: AtomicU64,
+    replay_accepted: AtomicU64,
+    replay_rejected: AtomicU64,
 }
 
 impl Counters {
@@ -93,24 +299,55 @@ impl Counters {
             rate_limited: AtomicU64::new(0),
             queue_full: AtomicU64::new(0),
             network_error: AtomicU64::new(0),
-            latency_sum_us: AtomicU64::new(0),
+            latency_overall: LatencyHistogram::new(),
+            latency_accepted: LatencyHistogram::new(),
+            latency_rejected: LatencyHistogram::new(),
+            latency_rate_limited: LatencyHistogram::new(),
+            latency_queue_full: LatencyHistogram::new(),
+            latency_other: LatencyHistogram::new(),
+            batches_sent: AtomicU64::new(0),
+            batch_sign_us: AtomicU64::new(0),
+            fuzz_clean_reject: AtomicU64::new(0),
+            fuzz_parse_error: AtomicU64::new(0),
+            fuzz_server_error: AtomicU64::new(0),
+            fuzz_timeout: AtomicU64::new(0),
+            skew_sent: AtomicU64::new(0),
+            skew_accepted: AtomicU64::new(0),
+            skew_rejected: AtomicU64::new(0),
+            max_accepted_skew_ms: AtomicI64::new(i64::MIN),
+            min_accepted_skew_ms: AtomicI64::new(i64::MAX),
+            replay_sent: AtomicU64::new(0),
+            replay_accepted: AtomicU64::new(0),
+            replay_rejected: AtomicU64::new(0),
         }
     }
 }
 
+/// Builds a signed packet. `skew_ms` is added to `timestamp_ms`/
+/// `created_at_ms` (clamped at zero) to probe the server's freshness window;
+/// pass `0` for a fresh packet. `replay_packet_id`, if given, reuses that id
+/// instead of generating a fresh one, to probe the server's dedup window.
+/// Returns the encoded payload and the `packet_id` it carries, so callers
+/// can remember it for a future `--replay-fraction` resend.
 fn build_signed_packet(
     sk: &SigningKey,
     rng: &mut impl Rng,
     lat: i32,
     lon: i32,
-) -> Vec<u8> {
+    skew_ms: i64,
+    replay_packet_id: Option<Vec<u8>>,
+) -> (Vec<u8>, Vec<u8>) {
     let now_ms = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
         .as_millis() as u64;
+    let ts_ms = (now_ms as i64 + skew_ms).max(0) as u64;
 
-    let mut packet_id = vec![0u8; 16];
-    rng.fill(&mut packet_id[..]);
+    let packet_id = replay_packet_id.unwrap_or_else(|| {
+        let mut id = vec![0u8; 16];
+        rng.fill(&mut id[..]);
+        id
+    });
 
     let vk = sk.verifying_key();
 
@@ -120,14 +357,18 @@ fn build_signed_packet(
         latitude_e7: lat + rng.gen_range(-1000..1000),
         longitude_e7: lon + rng.gen_range(-1000..1000),
         accuracy_cm: rng.gen_range(100..5000),
-        timestamp_ms: now_ms,
+        timestamp_ms: ts_ms,
         is_trapped: rng.gen_bool(0.3),
         packet_id: packet_id.clone(),
-        created_at_ms: now_ms,
+        created_at_ms: ts_ms,
         msg_type: rng.gen_range(1..=4),
         priority: rng.gen_range(1..=3),
         ed25519_signature: Vec::new(),
         ed25519_public_key: vk.to_bytes().to_vec(),
+        merkle_root: Vec::new(),
+        merkle_signature: Vec::new(),
+        merkle_proof: Vec::new(),
+        leaf_index: 0,
     };
 
     // Serialize without signature for signing
@@ -141,7 +382,244 @@ fn build_signed_packet(
     // Re-serialize with signature
     let mut final_bytes = Vec::with_capacity(p.encoded_len());
     p.encode(&mut final_bytes).unwrap();
-    final_bytes
+    (final_bytes, packet_id)
+}
+
+/// Builds `n` packets, signs them as one Merkle batch (one Ed25519 signature
+/// over the root instead of `n` independent signatures), and returns their
+/// encoded payloads plus the wall-clock time spent in the single `sk.sign`
+/// call — used to report the amortized per-packet signing cost.
+fn build_merkle_batch(
+    sk: &SigningKey,
+    rng: &mut impl Rng,
+    lat: i32,
+    lon: i32,
+    n: usize,
+) -> (Vec<Vec<u8>>, Duration) {
+    let vk = sk.verifying_key();
+
+    let mut packets: Vec<proto::SinyalistPacket> = (0..n)
+        .map(|_| {
+            let now_ms = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64;
+            let mut packet_id = vec![0u8; 16];
+            rng.fill(&mut packet_id[..]);
+            proto::SinyalistPacket {
+                user_id: rng.gen(),
+                latitude_e7: lat + rng.gen_range(-1000..1000),
+                longitude_e7: lon + rng.gen_range(-1000..1000),
+                accuracy_cm: rng.gen_range(100..5000),
+                timestamp_ms: now_ms,
+                is_trapped: rng.gen_bool(0.3),
+                packet_id,
+                created_at_ms: now_ms,
+                msg_type: rng.gen_range(1..=4),
+                priority: rng.gen_range(1..=3),
+                ed25519_signature: Vec::new(),
+                ed25519_public_key: vk.to_bytes().to_vec(),
+                merkle_root: Vec::new(),
+                merkle_signature: Vec::new(),
+                merkle_proof: Vec::new(),
+                leaf_index: 0,
+            }
+        })
+        .collect();
+
+    let leaves: Vec<merkle_batch::Hash> = packets
+        .iter()
+        .map(|p| {
+            let mut sb = Vec::with_capacity(p.encoded_len());
+            p.encode(&mut sb).unwrap();
+            merkle_batch::leaf_hash(&sb)
+        })
+        .collect();
+    let (root, proofs) = merkle_batch::build_tree(&leaves);
+
+    let sign_start = Instant::now();
+    let sig = sk.sign(&root);
+    let sign_time = sign_start.elapsed();
+
+    for (i, p) in packets.iter_mut().enumerate() {
+        p.merkle_root = root.to_vec();
+        p.merkle_signature = sig.to_bytes().to_vec();
+        p.merkle_proof = proofs[i].iter().map(|h| h.to_vec()).collect();
+        p.leaf_index = i as u32;
+    }
+
+    let payloads = packets
+        .iter()
+        .map(|p| {
+            let mut b = Vec::with_capacity(p.encoded_len());
+            p.encode(&mut b).unwrap();
+            b
+        })
+        .collect();
+    (payloads, sign_time)
+}
+
+/// Mutations applied by `--fuzz` to exercise the server's rejection paths —
+/// signature/protobuf-decode robustness, not just the happy path.
+#[derive(Clone, Copy, Debug)]
+enum FuzzMutation {
+    ByteFlip,
+    MultiByteFlip,
+    Truncate,
+    DuplicateField,
+    OversizedLength,
+    CorruptSignature,
+    MismatchedPublicKey,
+}
+
+const FUZZ_MUTATIONS: [FuzzMutation; 7] = [
+    FuzzMutation::ByteFlip,
+    FuzzMutation::MultiByteFlip,
+    FuzzMutation::Truncate,
+    FuzzMutation::DuplicateField,
+    FuzzMutation::OversizedLength,
+    FuzzMutation::CorruptSignature,
+    FuzzMutation::MismatchedPublicKey,
+];
+
+/// Builds a correctly-signed packet like `build_signed_packet`, then applies
+/// one randomly-chosen `FuzzMutation` before returning the final bytes.
+fn build_fuzz_packet(
+    sk: &SigningKey,
+    rng: &mut StdRng,
+    lat: i32,
+    lon: i32,
+) -> (Vec<u8>, FuzzMutation) {
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+
+    let mut packet_id = vec![0u8; 16];
+    rng.fill(&mut packet_id[..]);
+
+    let vk = sk.verifying_key();
+
+    let mut p = proto::SinyalistPacket {
+        user_id: rng.gen(),
+        latitude_e7: lat + rng.gen_range(-1000..1000),
+        longitude_e7: lon + rng.gen_range(-1000..1000),
+        accuracy_cm: rng.gen_range(100..5000),
+        timestamp_ms: now_ms,
+        is_trapped: rng.gen_bool(0.3),
+        packet_id,
+        created_at_ms: now_ms,
+        msg_type: rng.gen_range(1..=4),
+        priority: rng.gen_range(1..=3),
+        ed25519_signature: Vec::new(),
+        ed25519_public_key: vk.to_bytes().to_vec(),
+        merkle_root: Vec::new(),
+        merkle_signature: Vec::new(),
+        merkle_proof: Vec::new(),
+        leaf_index: 0,
+    };
+
+    let mut signing_bytes = Vec::with_capacity(p.encoded_len());
+    p.encode(&mut signing_bytes).unwrap();
+    let sig = sk.sign(&signing_bytes);
+    p.ed25519_signature = sig.to_bytes().to_vec();
+
+    let kind = FUZZ_MUTATIONS[rng.gen_range(0..FUZZ_MUTATIONS.len())];
+
+    // Mutations that need the structured packet (not just its encoded bytes)
+    // are applied before the final encode.
+    match kind {
+        FuzzMutation::CorruptSignature => {
+            if let Some(b) = p.ed25519_signature.first_mut() {
+                *b ^= 0xFF;
+            }
+        }
+        FuzzMutation::MismatchedPublicKey => {
+            let other = SigningKey::generate(&mut OsRng);
+            p.ed25519_public_key = other.verifying_key().to_bytes().to_vec();
+        }
+        _ => {}
+    }
+
+    let mut bytes = Vec::with_capacity(p.encoded_len());
+    p.encode(&mut bytes).unwrap();
+
+    match kind {
+        FuzzMutation::ByteFlip => {
+            if !bytes.is_empty() {
+                let i = rng.gen_range(0..bytes.len());
+                bytes[i] ^= 1 << rng.gen_range(0..8);
+            }
+        }
+        FuzzMutation::MultiByteFlip => {
+            for _ in 0..rng.gen_range(2..8) {
+                if bytes.is_empty() {
+                    break;
+                }
+                let i = rng.gen_range(0..bytes.len());
+                bytes[i] ^= 1 << rng.gen_range(0..8);
+            }
+        }
+        FuzzMutation::Truncate => {
+            let cut = rng.gen_range(0..=bytes.len());
+            bytes.truncate(cut);
+        }
+        FuzzMutation::DuplicateField => {
+            if bytes.len() > 4 {
+                let start = rng.gen_range(0..bytes.len() - 2);
+                let len = rng.gen_range(1..=(bytes.len() - start).min(8));
+                let dup = bytes[start..start + len].to_vec();
+                bytes.splice(start..start, dup);
+            }
+        }
+        FuzzMutation::OversizedLength => {
+            // Protobuf length-delimited fields are varint-prefixed; smashing
+            // a byte to 0xFF anywhere there's a length byte makes the decoder
+            // think a field is far longer than the remaining buffer.
+            if bytes.len() > 1 {
+                let i = rng.gen_range(0..bytes.len() - 1);
+                bytes[i] = 0xFF;
+            }
+        }
+        FuzzMutation::CorruptSignature | FuzzMutation::MismatchedPublicKey => {}
+    }
+
+    (bytes, kind)
+}
+
+/// Saves a mutated payload that triggered a 5xx or a timeout so it can be
+/// inspected or replayed later, independent of re-running the whole fuzz run.
+fn save_corpus_entry(dir: &str, seed: u64, tick: u64, mutation: FuzzMutation, payload: &[u8]) {
+    if std::fs::create_dir_all(dir).is_err() {
+        return;
+    }
+    let path = format!("{dir}/seed{seed}-tick{tick:06}-{mutation:?}.bin");
+    if let Err(e) = std::fs::write(&path, payload) {
+        eprintln!("Failed to save fuzz corpus entry {}: {}", path, e);
+    }
+}
+
+/// Dumps the full bucket counts of every latency histogram (overall + per
+/// status class) to `path` as CSV, so runs can be diffed across server
+/// configurations bucket-by-bucket instead of just comparing percentiles.
+fn write_latency_csv(path: &str, counters: &Counters) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut f = std::fs::File::create(path)?;
+    writeln!(f, "bucket_upper_us,overall,accepted,rejected,rate_limited,queue_full,other")?;
+    for i in 0..LATENCY_BUCKETS {
+        writeln!(
+            f,
+            "{},{},{},{},{},{},{}",
+            LatencyHistogram::bucket_upper_us(i),
+            counters.latency_overall.buckets[i].load(Ordering::Relaxed),
+            counters.latency_accepted.buckets[i].load(Ordering::Relaxed),
+            counters.latency_rejected.buckets[i].load(Ordering::Relaxed),
+            counters.latency_rate_limited.buckets[i].load(Ordering::Relaxed),
+            counters.latency_queue_full.buckets[i].load(Ordering::Relaxed),
+            counters.latency_other.buckets[i].load(Ordering::Relaxed),
+        )?;
+    }
+    Ok(())
 }
 
 fn main() {
@@ -161,6 +639,28 @@ fn main() {
         .collect();
 
     println!("Generated {} Ed25519 keypairs", keypairs.len());
+    if let Some(n) = args.batch {
+        println!("Merkle-batch mode: signing every {} packets under one signature", n);
+    }
+    let fuzz_seed = args.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut fuzz_rng = StdRng::seed_from_u64(fuzz_seed);
+    if args.fuzz {
+        println!(
+            "Fuzz mode: seed={} (pass --seed {} to replay this mutation sequence), corpus_dir={}",
+            fuzz_seed, fuzz_seed, args.corpus_dir
+        );
+    }
+    if args.skew_sweep {
+        println!(
+            "Clock-skew sweep: ramping from {}ms to +{}ms over the run",
+            -SKEW_SWEEP_MAX_MS, SKEW_SWEEP_MAX_MS
+        );
+    } else if let Some(skew) = args.clock_skew {
+        println!("Clock skew: {}ms on every packet", skew);
+    }
+    if let Some(p) = args.replay_fraction {
+        println!("Replay fraction: {:.1}% of packets resend a prior packet_id", p * 100.0);
+    }
 
     // Check server health
     let health_url = format!("{}/health", args.url);
@@ -192,10 +692,71 @@ fn main() {
 
     let mut rng = rand::thread_rng();
     let mut tick = 0u64;
+    let mut pending: VecDeque<Vec<u8>> = VecDeque::new();
+    let mut recent_packet_ids: VecDeque<Vec<u8>> = VecDeque::with_capacity(REPLAY_RING_SIZE);
 
     while Instant::now() < deadline {
         let key_idx = (tick as usize) % keypairs.len();
-        let payload = build_signed_packet(&keypairs[key_idx], &mut rng, args.lat, args.lon);
+
+        let mut fuzz_kind: Option<FuzzMutation> = None;
+        let mut skew_ms: Option<i64> = None;
+        let mut replayed = false;
+        let payload = match pending.pop_front() {
+            Some(payload) => payload,
+            None if args.fuzz => {
+                let (bytes, kind) =
+                    build_fuzz_packet(&keypairs[key_idx], &mut fuzz_rng, args.lat, args.lon);
+                fuzz_kind = Some(kind);
+                bytes
+            }
+            None => match args.batch {
+                Some(n) if n > 1 => {
+                    let (batch, sign_time) =
+                        build_merkle_batch(&keypairs[key_idx], &mut rng, args.lat, args.lon, n);
+                    counters.batches_sent.fetch_add(1, Ordering::Relaxed);
+                    counters
+                        .batch_sign_us
+                        .fetch_add(sign_time.as_micros() as u64, Ordering::Relaxed);
+                    pending.extend(batch);
+                    pending.pop_front().unwrap()
+                }
+                _ => {
+                    let skew = if args.skew_sweep {
+                        let progress = start.elapsed().as_secs_f64() / args.duration.max(1) as f64;
+                        -SKEW_SWEEP_MAX_MS + (progress.clamp(0.0, 1.0) * 2.0 * SKEW_SWEEP_MAX_MS as f64) as i64
+                    } else {
+                        args.clock_skew.unwrap_or(0)
+                    };
+                    if skew != 0 {
+                        skew_ms = Some(skew);
+                    }
+
+                    let replay_id = args
+                        .replay_fraction
+                        .filter(|_| !recent_packet_ids.is_empty())
+                        .filter(|&p| rng.gen_bool(p.clamp(0.0, 1.0)))
+                        .map(|_| recent_packet_ids.front().unwrap().clone());
+                    replayed = replay_id.is_some();
+
+                    let (bytes, packet_id) = build_signed_packet(
+                        &keypairs[key_idx],
+                        &mut rng,
+                        args.lat,
+                        args.lon,
+                        skew,
+                        replay_id,
+                    );
+                    if !replayed {
+                        recent_packet_ids.push_back(packet_id);
+                        if recent_packet_ids.len() > REPLAY_RING_SIZE {
+                            recent_packet_ids.pop_front();
+                        }
+                    }
+                    bytes
+                }
+            },
+        };
+        let corpus_payload = if args.fuzz { Some(payload.clone()) } else { None };
 
         let req_start = Instant::now();
         counters.sent.fetch_add(1, Ordering::Relaxed);
@@ -208,28 +769,74 @@ fn main() {
         {
             Ok(resp) => {
                 let lat = req_start.elapsed().as_micros() as u64;
-                counters.latency_sum_us.fetch_add(lat, Ordering::Relaxed);
+                counters.latency_overall.record(lat);
 
-                match resp.status().as_u16() {
+                let status = resp.status().as_u16();
+                match status {
                     200 => {
                         counters.accepted.fetch_add(1, Ordering::Relaxed);
+                        counters.latency_accepted.record(lat);
+                    }
+                    403 if args.fuzz => {
+                        counters.fuzz_clean_reject.fetch_add(1, Ordering::Relaxed);
+                        counters.latency_rejected.record(lat);
                     }
                     403 => {
                         counters.rejected.fetch_add(1, Ordering::Relaxed);
+                        counters.latency_rejected.record(lat);
                     }
                     429 => {
                         counters.rate_limited.fetch_add(1, Ordering::Relaxed);
+                        counters.latency_rate_limited.record(lat);
                     }
                     503 => {
                         counters.queue_full.fetch_add(1, Ordering::Relaxed);
+                        counters.latency_queue_full.record(lat);
+                    }
+                    s if args.fuzz && (400..500).contains(&s) => {
+                        counters.fuzz_parse_error.fetch_add(1, Ordering::Relaxed);
+                        counters.latency_other.record(lat);
+                    }
+                    s if args.fuzz && (500..600).contains(&s) => {
+                        counters.fuzz_server_error.fetch_add(1, Ordering::Relaxed);
+                        counters.latency_other.record(lat);
+                        if let (Some(kind), Some(payload)) = (fuzz_kind, &corpus_payload) {
+                            save_corpus_entry(&args.corpus_dir, fuzz_seed, tick, kind, payload);
+                        }
                     }
                     other => {
                         counters.rejected.fetch_add(1, Ordering::Relaxed);
+                        counters.latency_other.record(lat);
                         if tick < 5 {
                             eprintln!("Unexpected status: {}", other);
                         }
                     }
                 }
+
+                if let Some(skew) = skew_ms {
+                    counters.skew_sent.fetch_add(1, Ordering::Relaxed);
+                    if status == 200 {
+                        counters.skew_accepted.fetch_add(1, Ordering::Relaxed);
+                        counters.max_accepted_skew_ms.fetch_max(skew, Ordering::Relaxed);
+                        counters.min_accepted_skew_ms.fetch_min(skew, Ordering::Relaxed);
+                    } else {
+                        counters.skew_rejected.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                if replayed {
+                    counters.replay_sent.fetch_add(1, Ordering::Relaxed);
+                    if status == 200 {
+                        counters.replay_accepted.fetch_add(1, Ordering::Relaxed);
+                    } else {
+                        counters.replay_rejected.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+            Err(e) if args.fuzz && e.is_timeout() => {
+                counters.fuzz_timeout.fetch_add(1, Ordering::Relaxed);
+                if let (Some(kind), Some(payload)) = (fuzz_kind, &corpus_payload) {
+                    save_corpus_entry(&args.corpus_dir, fuzz_seed, tick, kind, payload);
+                }
             }
             Err(_) => {
                 counters.network_error.fetch_add(1, Ordering::Relaxed);
@@ -266,11 +873,6 @@ fn main() {
     let elapsed = start.elapsed();
     let sent = counters.sent.load(Ordering::Relaxed);
     let accepted = counters.accepted.load(Ordering::Relaxed);
-    let avg_lat = if sent > 0 {
-        counters.latency_sum_us.load(Ordering::Relaxed) / sent
-    } else {
-        0
-    };
 
     println!("\n=== Results ===");
     println!("Duration:     {:.2}s", elapsed.as_secs_f64());
@@ -280,6 +882,98 @@ fn main() {
     println!("Rate limited: {}", counters.rate_limited.load(Ordering::Relaxed));
     println!("Queue full:   {}", counters.queue_full.load(Ordering::Relaxed));
     println!("Net errors:   {}", counters.network_error.load(Ordering::Relaxed));
-    println!("Avg latency:  {} us", avg_lat);
     println!("Throughput:   {:.1} pkt/s", sent as f64 / elapsed.as_secs_f64());
+
+    println!("\n=== Latency (us) ===");
+    println!(
+        "{:<20} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8}",
+        "class", "n", "mean", "p50", "p90", "p99", "p99.9", "max"
+    );
+    for (label, hist) in [
+        ("overall", &counters.latency_overall),
+        ("accepted (200)", &counters.latency_accepted),
+        ("rejected (403)", &counters.latency_rejected),
+        ("rate_limited (429)", &counters.latency_rate_limited),
+        ("queue_full (503)", &counters.latency_queue_full),
+        ("other", &counters.latency_other),
+    ] {
+        if hist.count() == 0 {
+            continue;
+        }
+        println!(
+            "{:<20} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8}",
+            label,
+            hist.count(),
+            hist.mean(),
+            hist.percentile(0.50),
+            hist.percentile(0.90),
+            hist.percentile(0.99),
+            hist.percentile(0.999),
+            hist.max(),
+        );
+    }
+
+    if args.batch.is_some() {
+        let batches_sent = counters.batches_sent.load(Ordering::Relaxed);
+        let batch_sign_us = counters.batch_sign_us.load(Ordering::Relaxed);
+        let per_packet_sign_us = if sent > 0 {
+            batch_sign_us as f64 / sent as f64
+        } else {
+            0.0
+        };
+        println!("Batches sent: {}", batches_sent);
+        println!(
+            "Effective per-packet signing cost: {:.2} us (vs. {} whole Ed25519 signs for {} packets)",
+            per_packet_sign_us, batches_sent, sent
+        );
+    }
+
+    if args.fuzz {
+        println!("\n=== Fuzz Results (seed={}) ===", fuzz_seed);
+        println!("Clean rejects (403): {}", counters.fuzz_clean_reject.load(Ordering::Relaxed));
+        println!("4xx parse errors:    {}", counters.fuzz_parse_error.load(Ordering::Relaxed));
+        println!("5xx/crashes:         {}", counters.fuzz_server_error.load(Ordering::Relaxed));
+        println!("Timeouts:            {}", counters.fuzz_timeout.load(Ordering::Relaxed));
+        println!("Corpus saved to:     {}", args.corpus_dir);
+    }
+
+    if args.clock_skew.is_some() || args.skew_sweep {
+        let skew_sent = counters.skew_sent.load(Ordering::Relaxed);
+        println!("\n=== Clock-Skew Results ===");
+        println!(
+            "Skewed sent: {} accepted={} rejected={}",
+            skew_sent,
+            counters.skew_accepted.load(Ordering::Relaxed),
+            counters.skew_rejected.load(Ordering::Relaxed),
+        );
+        if args.skew_sweep {
+            let max_accepted = counters.max_accepted_skew_ms.load(Ordering::Relaxed);
+            let min_accepted = counters.min_accepted_skew_ms.load(Ordering::Relaxed);
+            if max_accepted == i64::MIN {
+                println!("Inferred max tolerated skew: no skewed packets were accepted");
+            } else {
+                println!(
+                    "Inferred max tolerated skew: ahead={}ms behind={}ms",
+                    max_accepted, min_accepted
+                );
+            }
+        }
+    }
+
+    if args.replay_fraction.is_some() {
+        println!("\n=== Replay Results ===");
+        println!(
+            "Replayed sent: {} accepted={} rejected={}",
+            counters.replay_sent.load(Ordering::Relaxed),
+            counters.replay_accepted.load(Ordering::Relaxed),
+            counters.replay_rejected.load(Ordering::Relaxed),
+        );
+    }
+
+    if let Some(path) = &args.latency_csv {
+        match write_latency_csv(path, &counters) {
+            Ok(()) => println!("\nLatency histogram written to {}", path),
+            Err(e) => eprintln!("Failed to write latency CSV {}: {}", path, e),
+        }
+    }
 }